@@ -14,7 +14,7 @@ use rswm::core;
 use rswm::core::XWindowManager;
 use rswm::extra;
 use rswm::loggers;
-use rswm::layout::{Tile, Tile13, FullScreen, Overview, Layout};
+use rswm::layout::{Tile, Bsp, FullScreen, Gaps, MasterStack, Overview, Split, Tabbed};
 use rswm::util::spawn;
 
 const MOD_MASK: c_uint = xlib::Mod4Mask;
@@ -72,29 +72,33 @@ fn main() {
               keysym::XK_w,
               Box::new(extra::add_window_user_tag_dmenu)),
              (MOD_MASK, keysym::XK_w, Box::new(extra::select_window_dmenu)),
+             (MOD_MASK, keysym::XK_a, Box::new(extra::select_workspace_dmenu)),
+             (MOD_MASK | xlib::ShiftMask,
+              keysym::XK_a,
+              Box::new(extra::rename_workspace_dmenu)),
              (MOD_MASK, keysym::XK_y, Box::new(|w| w.set_focus_index(Some(0)))),
              (MOD_MASK, keysym::XK_u, Box::new(|w| w.set_focus_index(Some(1)))),
              (MOD_MASK, keysym::XK_i, Box::new(|w| w.set_focus_index(Some(2)))),
              (MOD_MASK, keysym::XK_o, Box::new(|w| w.set_focus_index(Some(3)))),
              (MOD_MASK, keysym::XK_p, Box::new(|w| w.set_focus_index(None))),
              (MOD_MASK, keysym::XK_Tab, Box::new(|w| w.toggle_back())),
-             (MOD_MASK, keysym::XK_s, Box::new(toggle_sticky))];
+             (MOD_MASK, keysym::XK_s, Box::new(toggle_sticky)),
+             (MOD_MASK, keysym::XK_grave, Box::new(|w| w.toggle_scratchpad("terminal"))),
+             (MOD_MASK, keysym::XK_c, Box::new(|w| w.toggle_scratchpad("calculator")))];
 
     let start_programs: Vec<StartAction> =
         vec![Box::new(|| spawn("fcitx", &[])),
-             Box::new(|| spawn("tilda", &["--hidden"])),
              Box::new(|| spawn("/usr/lib/polkit-kde/polkit-kde-authentication-agent-1", &[]))];
 
     let rules: Vec<(ClientPredicate, ClientAction)> =
         vec![(Box::new(|c| c.get_class().as_str() == "Gimp"), Box::new(|c| c.set_floating(true))),
              (Box::new(|c| c.is_dialog()), Box::new(|c| c.set_floating(true))),
              (Box::new(|c| c.get_class().as_str() == "VirtualBox"),
-              Box::new(|c| c.set_floating(true))),
-             (Box::new(|c| c.get_class().as_str() == "Tilda"),
-              Box::new(|c| {
-                  c.set_floating(true);
-                  c.set_sticky(true);
-              }))];
+              Box::new(|c| c.set_floating(true)))];
+
+    let scratchpads =
+        vec![Scratchpad::with_title("terminal", "urxvt", &["-title", "scratchpad"], "scratchpad"),
+             Scratchpad::with_class("calculator", "galculator", &[], "Galculator").size(0.4)];
 
     let tag_description: Vec<(c_uchar, String)> = vec![('1' as c_uchar, "web".into()),
                                                        ('2' as c_uchar, "code".into())];
@@ -106,13 +110,24 @@ fn main() {
         .start_programs(start_programs)
         .tag_keys(define_tags!(MOD_MASK,
                                xlib::ShiftMask,
+                               xlib::ControlMask | xlib::ShiftMask,
+                               xlib::ControlMask,
+                               xlib::Mod1Mask,
                                ['1', '2', '3', '4', '5', '6', '7', '8', '9']))
         .tag_default('1' as c_uchar)
         .rules(rules)
+        .scratchpads(scratchpads)
         .tag_description(tag_description)
-        .tag_layout(vec![('3' as c_uchar, Box::new(Tile13 { layout: Box::new(FullScreen) })),
-                         ('9' as c_uchar, Box::new(FullScreen)),
-                         (TAG_OVERVIEW as c_uchar, Box::new(Overview))]);
+        .tag_output(vec![('9' as c_uchar, "HDMI-1".into())])
+        .tag_layout(vec![('3' as c_uchar,
+                          vec![Box::new(Split::tile13(Box::new(FullScreen))),
+                               Box::new(Tile)]),
+                         ('4' as c_uchar,
+                          vec![Box::new(Gaps::new(Box::new(MasterStack::default()))),
+                               Box::new(FullScreen)]),
+                         ('5' as c_uchar, vec![Box::new(Bsp::default()), Box::new(FullScreen)]),
+                         ('9' as c_uchar, vec![Box::new(Tabbed), Box::new(FullScreen)]),
+                         (TAG_OVERVIEW as c_uchar, vec![Box::new(Overview)])]);
     let logger_config = loggers::LoggerConfig::default()
         .client_title_length(8)
         .client_template("<fc=#CCCCCC,#006048> <{{& tag }}> {{& content }} </fc>")
@@ -130,7 +145,7 @@ fn main() {
 fn toggle_sticky(w: &mut core::WindowManager) {
     if let Some(c) = w.current_focused() {
         let sticky = c.is_sticky();
-        c.clone().set_sticky(!sticky);
+        w.set_sticky(c.clone(), !sticky);
         c.clone().set_floating(!sticky);
         w.arrange_windows();
     }
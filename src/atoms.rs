@@ -86,6 +86,31 @@ pub fn net_supporting_wm_check() -> xlib::Atom {
     unsafe { NET_SUPPORTING_WM_CHECK }
 }
 
+static mut NET_NUMBER_OF_DESKTOPS: xlib::Atom = 0;
+pub fn net_number_of_desktops() -> xlib::Atom {
+    unsafe { NET_NUMBER_OF_DESKTOPS }
+}
+
+static mut NET_CURRENT_DESKTOP: xlib::Atom = 0;
+pub fn net_current_desktop() -> xlib::Atom {
+    unsafe { NET_CURRENT_DESKTOP }
+}
+
+static mut NET_DESKTOP_NAMES: xlib::Atom = 0;
+pub fn net_desktop_names() -> xlib::Atom {
+    unsafe { NET_DESKTOP_NAMES }
+}
+
+static mut NET_WM_DESKTOP: xlib::Atom = 0;
+pub fn net_wm_desktop() -> xlib::Atom {
+    unsafe { NET_WM_DESKTOP }
+}
+
+static mut UTF8_STRING: xlib::Atom = 0;
+pub fn utf8_string() -> xlib::Atom {
+    unsafe { UTF8_STRING }
+}
+
 pub fn create_atoms(display: *mut xlib::Display) {
     unsafe {
         WM_PROTOCOLS = intern_atom(display, "WM_PROTOCOLS");
@@ -94,7 +119,7 @@ pub fn create_atoms(display: *mut xlib::Display) {
         WM_TAKE_FOCUS = intern_atom(display, "WM_TAKE_FOCUS");
         NET_ACTIVE_WINDOW = intern_atom(display, "_NET_ACTIVE_WINDOW");
         NET_SUPPORTED = intern_atom(display, "_NET_SUPPORTED");
-        NET_WM_NAME = intern_atom(display, "_NET_SUPPORTED");
+        NET_WM_NAME = intern_atom(display, "_NET_WM_NAME");
         NET_WM_STATE = intern_atom(display, "_NET_WM_STATE");
         NET_WM_STATE_ABOVE = intern_atom(display, "_NET_WM_STATE_ABOVE");
         NET_WM_STATE_STICKY = intern_atom(display, "_NET_WM_STATE_STICKY");
@@ -104,7 +129,12 @@ pub fn create_atoms(display: *mut xlib::Display) {
         NET_WM_WINDOW_TYPE_DIALOG = intern_atom(display, "_NET_WM_WINDOW_TYPE_DIALOG");
         NET_WM_WINDOW_TYPE_DOCK = intern_atom(display, "_NET_WM_WINDOW_TYPE_DOCK");
         NET_CLIENT_LIST = intern_atom(display, "_NET_CLIENT_LIST");
-        NET_SUPPORTING_WM_CHECK = intern_atom(display, "_NET_SUPPORTING_WM_CHECK")
+        NET_SUPPORTING_WM_CHECK = intern_atom(display, "_NET_SUPPORTING_WM_CHECK");
+        NET_NUMBER_OF_DESKTOPS = intern_atom(display, "_NET_NUMBER_OF_DESKTOPS");
+        NET_CURRENT_DESKTOP = intern_atom(display, "_NET_CURRENT_DESKTOP");
+        NET_DESKTOP_NAMES = intern_atom(display, "_NET_DESKTOP_NAMES");
+        NET_WM_DESKTOP = intern_atom(display, "_NET_WM_DESKTOP");
+        UTF8_STRING = intern_atom(display, "UTF8_STRING")
     }
 }
 
@@ -123,7 +153,7 @@ pub fn get_atom(atom: xlib::Atom) -> String {
         } else if atom == NET_SUPPORTED {
             "_NET_SUPPORTED".to_string()
         } else if atom == NET_WM_NAME {
-            "_NET_SUPPORTED".to_string()
+            "_NET_WM_NAME".to_string()
         } else if atom == NET_WM_STATE {
             "_NET_WM_STATE".to_string()
         } else if atom == NET_WM_STATE_ABOVE {
@@ -142,6 +172,18 @@ pub fn get_atom(atom: xlib::Atom) -> String {
             "_NET_WM_WINDOW_TYPE_DOCK".to_string()
         } else if atom == NET_CLIENT_LIST {
             "_NET_CLIENT_LIST".to_string()
+        } else if atom == NET_SUPPORTING_WM_CHECK {
+            "_NET_SUPPORTING_WM_CHECK".to_string()
+        } else if atom == NET_NUMBER_OF_DESKTOPS {
+            "_NET_NUMBER_OF_DESKTOPS".to_string()
+        } else if atom == NET_CURRENT_DESKTOP {
+            "_NET_CURRENT_DESKTOP".to_string()
+        } else if atom == NET_DESKTOP_NAMES {
+            "_NET_DESKTOP_NAMES".to_string()
+        } else if atom == NET_WM_DESKTOP {
+            "_NET_WM_DESKTOP".to_string()
+        } else if atom == UTF8_STRING {
+            "UTF8_STRING".to_string()
         } else {
             format!("Unknown atom: {}", atom)
         }
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::cmp;
 use std::cell::{Ref, RefCell, RefMut};
 use std::collections::HashMap;
 use std::ffi::CString;
@@ -14,7 +15,7 @@ use atoms;
 use config::Config;
 use util;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Rect {
     pub x: c_int,
     pub y: c_int,
@@ -84,6 +85,12 @@ pub struct Client {
     base_height: c_int,
     width_inc: c_int,
     height_inc: c_int,
+    min_width: c_int,
+    min_height: c_int,
+    max_width: c_int,
+    max_height: c_int,
+    min_aspect: f32,
+    max_aspect: f32,
     border: c_int,
     old_border: c_int,
     weight: i32,
@@ -120,13 +127,19 @@ impl Client {
             base_height: 0,
             width_inc: 1,
             height_inc: 1,
+            min_width: 0,
+            min_height: 0,
+            max_width: 0,
+            max_height: 0,
+            min_aspect: 0.0,
+            max_aspect: 0.0,
             focused_border_color: 0,
             normal_border_color: 0,
             old_rect: Rect::default(),
             rect: Rect::default(),
             border: 0,
             old_border: 0,
-            weight: -1,
+            weight: 1,
             extras: HashMap::new(),
         };
         unsafe {
@@ -135,6 +148,12 @@ impl Client {
                 client.class =
                     Rc::new(CString::from_raw(class_hint.res_class).to_string_lossy().into_owned());
             }
+            // The border the window was created with, so that setting another one is never
+            // skipped as a no-op.
+            let mut attributes: xlib::XWindowAttributes = zeroed();
+            if xlib::XGetWindowAttributes(display, window, &mut attributes) != 0 {
+                client.border = attributes.border_width;
+            }
         }
         client
     }
@@ -269,6 +288,15 @@ impl ClientW {
         self.borrow_mut().is_above = above;
     }
 
+    /// Share of the space of a weighted layout the client gets, relative to its neighbours.
+    pub fn weight(&self) -> i32 {
+        self.borrow().weight
+    }
+
+    pub fn set_weight(&mut self, weight: i32) {
+        self.borrow_mut().weight = cmp::max(weight, 1);
+    }
+
     pub fn set_maximized(&mut self, maximized: bool) {
         self.borrow_mut().is_maximized = maximized;
     }
@@ -284,8 +312,8 @@ impl ClientW {
                                       xlib::PropModeReplace,
                                       (&atoms::net_wm_state_fullscreen() as *const c_ulong) as *const u8,
                                       1);
-                xlib::XSetWindowBorderWidth(self.display(), self.window(), 0);
             }
+            self.set_border_width(0);
             self.borrow_mut().is_fullscreen = true;
             self.set_floating(true);
             self.resize(rect, false);
@@ -303,10 +331,9 @@ impl ClientW {
                                       xlib::PropModeReplace,
                                       null(),
                                       0);
-                xlib::XSetWindowBorderWidth(self.display(),
-                                            self.window(),
-                                            self.borrow().config.border_width as c_uint);
             }
+            let border_width = self.borrow().config.border_width;
+            self.set_border_width(border_width);
             let was_floating = self.borrow().was_floating;
             self.set_floating(was_floating);
             self.borrow_mut().is_fullscreen = false;
@@ -365,15 +392,21 @@ impl ClientW {
     }
 
     pub fn resize(&mut self, rect: Rect, temporary: bool) {
+        let mut rect = rect;
         if !temporary {
             self.save_window_size();
             self.set_size(rect.x, rect.y, rect.width, rect.height);
+            // Tiled windows have their hints applied by the workspace, if at all.
+            if self.is_floating() && !self.is_fullscreen() {
+                rect = self.get_rect();
+            }
         }
         let mut xc: xlib::XWindowChanges = unsafe { zeroed() };
         xc.x = rect.x;
         xc.y = rect.y;
-        xc.width = rect.width;
-        xc.height = rect.height;
+        // X refuses empty windows, which layouts hand out when they run out of room.
+        xc.width = cmp::max(rect.width, 1);
+        xc.height = cmp::max(rect.height, 1);
         unsafe {
             xlib::XConfigureWindow(self.display(),
                                    self.window(),
@@ -464,6 +497,16 @@ impl ClientW {
         }
     }
 
+    pub fn set_border_width(&mut self, width: c_int) {
+        if self.borrow().border == width {
+            return;
+        }
+        self.borrow_mut().border = width;
+        unsafe {
+            xlib::XSetWindowBorderWidth(self.display(), self.window(), width as c_uint);
+        }
+    }
+
     pub fn set_border_color(&mut self, normal: c_ulong, focused: c_ulong) {
         self.borrow_mut().focused_border_color = focused;
         self.borrow_mut().normal_border_color = normal;
@@ -531,8 +574,11 @@ impl ClientW {
     }
 
     pub fn set_size(&mut self, x: c_int, y: c_int, width: c_int, height: c_int) {
-        let new_width = -(width - self.borrow().base_width) % self.borrow().width_inc + width;
-        let new_height = -(height - self.borrow().base_height) % self.borrow().height_inc + height;
+        let (new_width, new_height) = if self.is_fullscreen() {
+            (width, height)
+        } else {
+            self.apply_size_hints(width, height)
+        };
         debug!("setting size to: {}, {} instead of {}, {}",
                new_width,
                new_height,
@@ -547,6 +593,56 @@ impl ClientW {
         self.borrow_mut().rect = rect;
     }
 
+    /// Adjusts a window size to the size hints of the client: aspect ratio, resize
+    /// increments, min and max size, in the order ICCCM asks for.
+    pub fn apply_size_hints(&self, width: c_int, height: c_int) -> (c_int, c_int) {
+        let c = self.borrow();
+        let base_is_min = c.base_width == c.min_width && c.base_height == c.min_height;
+        let (mut w, mut h) = (width, height);
+        if !base_is_min {
+            // Aspect ratio does not include the base size, unless it is also the min size.
+            w -= c.base_width;
+            h -= c.base_height;
+        }
+        if c.min_aspect > 0.0 && c.max_aspect > 0.0 && w > 0 && h > 0 {
+            if c.max_aspect < w as f32 / h as f32 {
+                w = (h as f32 * c.max_aspect + 0.5) as c_int;
+            } else if c.min_aspect < h as f32 / w as f32 {
+                h = (w as f32 * c.min_aspect + 0.5) as c_int;
+            }
+        }
+        if base_is_min {
+            w -= c.base_width;
+            h -= c.base_height;
+        }
+        if c.width_inc > 0 {
+            w -= w % c.width_inc;
+        }
+        if c.height_inc > 0 {
+            h -= h % c.height_inc;
+        }
+        w = cmp::max(w + c.base_width, c.min_width);
+        h = cmp::max(h + c.base_height, c.min_height);
+        if c.max_width > 0 {
+            w = cmp::min(w, c.max_width);
+        }
+        if c.max_height > 0 {
+            h = cmp::min(h, c.max_height);
+        }
+        (cmp::max(w, 1), cmp::max(h, 1))
+    }
+
+    /// Clamps the client to the size its hints allow within rect, centered in rect.
+    pub fn fit_size_hints(&self, rect: Rect) -> Rect {
+        let (width, height) = self.apply_size_hints(rect.width, rect.height);
+        let width = cmp::min(width, rect.width);
+        let height = cmp::min(height, rect.height);
+        Rect::new(rect.x + (rect.width - width) / 2,
+                  rect.y + (rect.height - height) / 2,
+                  width,
+                  height)
+    }
+
     pub fn save_window_size(&mut self) {
         let rect = self.borrow().rect.clone();
         self.borrow_mut().old_rect = rect;
@@ -567,14 +663,53 @@ impl ClientW {
         if size.flags & xlib::PBaseSize != 0 {
             self.borrow_mut().base_width = size.base_width;
             self.borrow_mut().base_height = size.base_height;
+        } else if size.flags & xlib::PMinSize != 0 {
+            self.borrow_mut().base_width = size.min_width;
+            self.borrow_mut().base_height = size.min_height;
+        } else {
+            self.borrow_mut().base_width = 0;
+            self.borrow_mut().base_height = 0;
         }
+        // Hints the client no longer sets are dropped rather than kept from before.
         if size.flags & xlib::PResizeInc != 0 {
             self.borrow_mut().width_inc = size.width_inc;
             self.borrow_mut().height_inc = size.height_inc;
             debug!("updating hint: width_inc {}, height_inc {}",
                    size.width_inc,
                    size.height_inc);
+        } else {
+            self.borrow_mut().width_inc = 1;
+            self.borrow_mut().height_inc = 1;
+        }
+        if size.flags & xlib::PMinSize != 0 {
+            self.borrow_mut().min_width = size.min_width;
+            self.borrow_mut().min_height = size.min_height;
+        } else if size.flags & xlib::PBaseSize != 0 {
+            self.borrow_mut().min_width = size.base_width;
+            self.borrow_mut().min_height = size.base_height;
+        } else {
+            self.borrow_mut().min_width = 0;
+            self.borrow_mut().min_height = 0;
+        }
+        if size.flags & xlib::PMaxSize != 0 {
+            self.borrow_mut().max_width = size.max_width;
+            self.borrow_mut().max_height = size.max_height;
+        } else {
+            self.borrow_mut().max_width = 0;
+            self.borrow_mut().max_height = 0;
+        }
+        if size.flags & xlib::PAspect != 0 && size.min_aspect.x > 0 && size.max_aspect.y > 0 {
+            self.borrow_mut().min_aspect = size.min_aspect.y as f32 / size.min_aspect.x as f32;
+            self.borrow_mut().max_aspect = size.max_aspect.x as f32 / size.max_aspect.y as f32;
+        } else {
+            self.borrow_mut().min_aspect = 0.0;
+            self.borrow_mut().max_aspect = 0.0;
         }
+        debug!("updating hint: min {}x{}, max {}x{}",
+               self.borrow().min_width,
+               self.borrow().min_height,
+               self.borrow().max_width,
+               self.borrow().max_height);
     }
 }
 
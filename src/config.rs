@@ -5,19 +5,30 @@ use x11::{xlib, keysym};
 
 use core::WindowManager;
 use client::{ClientL, ClientW};
-use layout::Layout;
+use layout::{Direction, FullScreen, Layout, LayoutMessage, MasterStack, Tile};
 
 const FOCUSED_BORDER_COLOR: &'static str = "RGBi:0.0/1.0/1.0";
 const NORMAL_BORDER_COLOR: &'static str = "RGBi:0.0/0.3/0.3";
 
 const BORDER_WIDTH: c_int = 3;
+const GAP_INNER: c_int = 10;
+const GAP_OUTER: c_int = 10;
+const GAP_DELTA: c_int = 2;
 const OVERVIEW_INSET: c_int = 15;
 const BAR_HEIGHT: c_int = 15;
+const TAB_HEIGHT: c_int = 18;
 
 const WINDOW_MOVE_DELTA: c_int = 15;
 const WINDOW_EXPAND_DELTA: c_int = 10;
+const MASTER_RATIO_DELTA: f32 = 0.05;
 
 pub const TAG_OVERVIEW: c_uchar = 0 as c_uchar;
+// Shows the clients of several tags at once.
+pub const TAG_UNION: c_uchar = 1 as c_uchar;
+// Holds the scratchpads that are put away.
+pub const TAG_SCRATCHPAD: c_uchar = 2 as c_uchar;
+
+const SCRATCHPAD_SIZE: f32 = 0.6;
 
 pub type WmAction = Box<Fn(&mut WindowManager)>;
 pub type ClientPredicate = Box<Fn(&ClientW) -> bool>;
@@ -28,18 +39,28 @@ pub struct Config {
     pub mod_key: c_uint,
     pub add_keys: Vec<(c_uint, c_uint, WmAction)>,
     pub bar_height: c_int,
+    pub bar_outputs: Vec<String>,
     pub border_width: c_int,
     pub focused_border_color: &'static str,
+    pub gap_inner: c_int,
+    pub gap_outer: c_int,
     pub keys: Vec<(c_uint, c_uint, WmAction)>,
+    pub layouts: Vec<Box<Layout + 'static>>,
     pub normal_border_color: &'static str,
     pub overview_inset: c_int,
+    pub remove_empty_workspaces: bool,
+    pub respect_size_hints: bool,
     pub rules: Vec<(ClientPredicate, ClientAction)>,
+    pub scratchpads: Vec<Scratchpad>,
+    pub smart_borders: bool,
     pub start_programs: Vec<StartAction>,
+    pub tab_height: c_int,
     pub tags: Vec<c_uchar>,
     pub tag_default: c_uchar,
     pub tag_description: Vec<(c_uchar, String)>,
     pub tag_keys: Vec<(c_uint, c_uint, WmAction)>,
-    pub tag_layout: Vec<(c_uchar, Box<Layout + 'static>)>,
+    pub tag_layout: Vec<(c_uchar, Vec<Box<Layout + 'static>>)>,
+    pub tag_output: Vec<(c_uchar, String)>,
     pub window_expand_delta: c_int,
     pub window_move_delta: c_int,
 }
@@ -50,6 +71,14 @@ impl Config {
             vec![(mod_mask, keysym::XK_q, Box::new(|w| process::exit(0))),
                  (mod_mask, keysym::XK_j, Box::new(|w| w.shift_focus(1))),
                  (mod_mask, keysym::XK_k, Box::new(|w| w.shift_focus(-1))),
+                 (mod_mask | xlib::ControlMask, keysym::XK_j, Box::new(|w| w.focus_monitor(1))),
+                 (mod_mask | xlib::ControlMask, keysym::XK_k, Box::new(|w| w.focus_monitor(-1))),
+                 (mod_mask | xlib::ControlMask | xlib::ShiftMask,
+                  keysym::XK_j,
+                  Box::new(|w| w.send_to_monitor(1))),
+                 (mod_mask | xlib::ControlMask | xlib::ShiftMask,
+                  keysym::XK_k,
+                  Box::new(|w| w.send_to_monitor(-1))),
                  (mod_mask, keysym::XK_F4, Box::new(|w| w.kill_client())),
                  (mod_mask, keysym::XK_m, Box::new(|w| w.toggle_maximize())),
                  (mod_mask, keysym::XK_e, Box::new(|w| w.toggle_floating())),
@@ -70,6 +99,67 @@ impl Config {
                   keysym::XK_Left,
                   Box::new(|w| w.expand_width(-WINDOW_EXPAND_DELTA))),
                  (mod_mask, keysym::XK_F2, Box::new(|w| w.select_tag(TAG_OVERVIEW))),
+                 (mod_mask,
+                  keysym::XK_comma,
+                  Box::new(|w| w.send_layout_message(LayoutMessage::IncMaster(1)))),
+                 (mod_mask,
+                  keysym::XK_period,
+                  Box::new(|w| w.send_layout_message(LayoutMessage::IncMaster(-1)))),
+                 (mod_mask,
+                  keysym::XK_equal,
+                  Box::new(|w| {
+                      w.send_layout_message(LayoutMessage::ResizeMaster(MASTER_RATIO_DELTA))
+                  })),
+                 (mod_mask,
+                  keysym::XK_minus,
+                  Box::new(|w| {
+                      w.send_layout_message(LayoutMessage::ResizeMaster(-MASTER_RATIO_DELTA))
+                  })),
+                 (mod_mask | xlib::ControlMask,
+                  keysym::XK_equal,
+                  Box::new(|w| w.resize_split(MASTER_RATIO_DELTA))),
+                 (mod_mask | xlib::ControlMask,
+                  keysym::XK_minus,
+                  Box::new(|w| w.resize_split(-MASTER_RATIO_DELTA))),
+                 (mod_mask | xlib::ControlMask,
+                  keysym::XK_h,
+                  Box::new(|w| {
+                      w.send_layout_message(LayoutMessage::Presel(Direction::Horizontal))
+                  })),
+                 (mod_mask | xlib::ControlMask,
+                  keysym::XK_v,
+                  Box::new(|w| w.send_layout_message(LayoutMessage::Presel(Direction::Vertical)))),
+                 (mod_mask | xlib::ShiftMask, keysym::XK_equal, Box::new(|w| w.inc_weight(1))),
+                 (mod_mask | xlib::ShiftMask, keysym::XK_minus, Box::new(|w| w.inc_weight(-1))),
+                 (mod_mask,
+                  keysym::XK_slash,
+                  Box::new(|w| w.send_layout_message(LayoutMessage::Rotate))),
+                 (mod_mask,
+                  keysym::XK_BackSpace,
+                  Box::new(|w| w.send_layout_message(LayoutMessage::Reset))),
+                 (mod_mask, keysym::XK_bracketright, Box::new(|w| w.inc_gaps(GAP_DELTA))),
+                 (mod_mask, keysym::XK_bracketleft, Box::new(|w| w.inc_gaps(-GAP_DELTA))),
+                 (mod_mask, keysym::XK_g, Box::new(|w| w.toggle_gaps())),
+                 (mod_mask,
+                  keysym::XK_x,
+                  Box::new(|w| w.send_layout_message(LayoutMessage::ToggleMirror))),
+                 (mod_mask | xlib::ShiftMask,
+                  keysym::XK_x,
+                  Box::new(|w| w.send_layout_message(LayoutMessage::ToggleRotate))),
+                 (mod_mask | xlib::ShiftMask,
+                  keysym::XK_m,
+                  Box::new(|w| w.send_layout_message(LayoutMessage::ToggleMagnifier))),
+                 (mod_mask | xlib::ControlMask,
+                  keysym::XK_Left,
+                  Box::new(|w| w.move_workspace(-1))),
+                 (mod_mask | xlib::ControlMask,
+                  keysym::XK_Right,
+                  Box::new(|w| w.move_workspace(1))),
+                 (mod_mask | xlib::ControlMask,
+                  keysym::XK_BackSpace,
+                  Box::new(|w| w.delete_workspace())),
+                 (mod_mask, keysym::XK_space, Box::new(|w| w.next_layout())),
+                 (mod_mask | xlib::ShiftMask, keysym::XK_space, Box::new(|w| w.prev_layout())),
                  (mod_mask,
                   keysym::XK_Return,
                   Box::new(|w| {
@@ -84,23 +174,36 @@ impl Config {
             }))];
         let (tag_keys, tags) = define_tags!(mod_mask,
                                             xlib::ShiftMask,
+                                            xlib::ControlMask | xlib::ShiftMask,
+                                            xlib::ControlMask,
+                                            xlib::Mod1Mask,
                                             ['1', '2', '3', '4', '5', '6', '7', '8', '9', '0']);
         Config {
             mod_key: mod_mask,
             add_keys: Vec::new(),
             bar_height: BAR_HEIGHT,
+            bar_outputs: Vec::new(),
             border_width: BORDER_WIDTH,
             focused_border_color: FOCUSED_BORDER_COLOR,
+            gap_inner: GAP_INNER,
+            gap_outer: GAP_OUTER,
             normal_border_color: NORMAL_BORDER_COLOR,
             keys: keys,
+            layouts: vec![Box::new(Tile), Box::new(MasterStack::default()), Box::new(FullScreen)],
             overview_inset: OVERVIEW_INSET,
+            remove_empty_workspaces: false,
+            respect_size_hints: false,
             rules: vec![],
+            scratchpads: vec![],
+            smart_borders: false,
             start_programs: vec![],
+            tab_height: TAB_HEIGHT,
             tag_default: tags[0],
             tags: tags,
             tag_description: vec![],
             tag_keys: tag_keys,
             tag_layout: Vec::new(),
+            tag_output: Vec::new(),
             window_expand_delta: WINDOW_EXPAND_DELTA,
             window_move_delta: WINDOW_MOVE_DELTA,
         }
@@ -116,6 +219,13 @@ impl Config {
         self
     }
 
+    /// Leaves room for the bar only on the monitors of the given RandR outputs, e.g.
+    /// "HDMI-1", instead of on every monitor.
+    pub fn bar_outputs(mut self, outputs: &[&str]) -> Config {
+        self.bar_outputs = outputs.iter().map(|o| o.to_string()).collect();
+        self
+    }
+
     pub fn border_width(mut self, border_width: c_int) -> Config {
         self.border_width = border_width;
         self
@@ -126,11 +236,26 @@ impl Config {
         self
     }
 
+    pub fn gap_inner(mut self, gap: c_int) -> Config {
+        self.gap_inner = gap;
+        self
+    }
+
+    pub fn gap_outer(mut self, gap: c_int) -> Config {
+        self.gap_outer = gap;
+        self
+    }
+
     pub fn keys(mut self, keys: Vec<(c_uint, c_uint, WmAction)>) -> Config {
         self.keys = keys;
         self
     }
 
+    pub fn layouts(mut self, layouts: Vec<Box<Layout + 'static>>) -> Config {
+        self.layouts = layouts;
+        self
+    }
+
     pub fn no_default_keys(mut self) -> Config {
         self.tag_keys = Vec::new();
         self
@@ -146,16 +271,48 @@ impl Config {
         self
     }
 
+    /// Deletes workspaces once they are empty and out of view, so that workspaces can come
+    /// and go by name.
+    pub fn remove_empty_workspaces(mut self, remove: bool) -> Config {
+        self.remove_empty_workspaces = remove;
+        self
+    }
+
+    /// Applies the size hints of tiled windows, keeping e.g. fixed size dialogs at their
+    /// size and centering them in their tiles. Floating windows always respect hints.
+    pub fn respect_size_hints(mut self, respect: bool) -> Config {
+        self.respect_size_hints = respect;
+        self
+    }
+
     pub fn rules(mut self, rules: Vec<(ClientPredicate, ClientAction)>) -> Config {
         self.rules = rules;
         self
     }
 
+    /// Windows that are called up by name and put away again, see Scratchpad.
+    pub fn scratchpads(mut self, scratchpads: Vec<Scratchpad>) -> Config {
+        self.scratchpads = scratchpads;
+        self
+    }
+
+    /// Draws no borders around a tiled window when it is the only one visible, e.g. when
+    /// it is alone on its workspace or the layout shows one window at a time.
+    pub fn smart_borders(mut self, smart_borders: bool) -> Config {
+        self.smart_borders = smart_borders;
+        self
+    }
+
     pub fn start_programs(mut self, start: Vec<StartAction>) -> Config {
         self.start_programs = start;
         self
     }
 
+    pub fn tab_height(mut self, tab_height: c_int) -> Config {
+        self.tab_height = tab_height;
+        self
+    }
+
     pub fn tag_default(mut self, tag: c_uchar) -> Config {
         self.tag_default = tag;
         self
@@ -169,14 +326,22 @@ impl Config {
     pub fn tag_keys(mut self, keys: (Vec<(c_uint, c_uint, WmAction)>, Vec<c_uchar>)) -> Config {
         self.tag_keys = keys.0;
         self.tag_default = keys.1[0];
+        self.tags = keys.1;
         self
     }
 
-    pub fn tag_layout(mut self, layout: Vec<(c_uchar, Box<Layout + 'static>)>) -> Config {
+    pub fn tag_layout(mut self, layout: Vec<(c_uchar, Vec<Box<Layout + 'static>>)>) -> Config {
         self.tag_layout = layout;
         self
     }
 
+    /// Pins workspaces to the monitors of RandR outputs, e.g. "eDP-1". A pinned workspace is
+    /// shown on its monitor whenever the output is connected.
+    pub fn tag_output(mut self, output: Vec<(c_uchar, String)>) -> Config {
+        self.tag_output = output;
+        self
+    }
+
     pub fn window_expand_delta(mut self, delta: c_int) -> Config {
         self.window_expand_delta = delta;
         self
@@ -187,6 +352,15 @@ impl Config {
         self
     }
 
+    pub fn get_output(&self, tag: c_uchar) -> Option<&str> {
+        for o in self.tag_output.iter() {
+            if o.0 == tag {
+                return Some(&o.1);
+            }
+        }
+        None
+    }
+
     pub fn get_description(&self, tag: c_uchar) -> Option<&str> {
         for c in self.tag_description.iter() {
             if c.0 == tag {
@@ -196,3 +370,52 @@ impl Config {
         None
     }
 }
+
+/// The default configuration with Mod4 as modifier. Building a config does not talk to the
+/// X server, so this is also what layouts can be exercised with in tests.
+impl Default for Config {
+    fn default() -> Config {
+        Config::new(xlib::Mod4Mask)
+    }
+}
+
+/// A window that is brought onto the current workspace and put away again by name. See
+/// WindowManager::toggle_scratchpad.
+pub struct Scratchpad {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    /// Tells the window of the scratchpad from other windows.
+    pub matcher: ClientPredicate,
+    /// Width and height of the window relative to the screen it is shown on.
+    pub size: f32,
+}
+
+impl Scratchpad {
+    pub fn new(name: &str, command: &str, args: &[&str], matcher: ClientPredicate) -> Self {
+        Scratchpad {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            matcher: matcher,
+            size: SCRATCHPAD_SIZE,
+        }
+    }
+
+    /// A scratchpad whose window is the one of class.
+    pub fn with_class(name: &str, command: &str, args: &[&str], class: &str) -> Self {
+        let class = class.to_string();
+        Scratchpad::new(name, command, args, Box::new(move |c| *c.get_class() == class))
+    }
+
+    /// A scratchpad whose window is the one titled title.
+    pub fn with_title(name: &str, command: &str, args: &[&str], title: &str) -> Self {
+        let title = title.to_string();
+        Scratchpad::new(name, command, args, Box::new(move |c| *c.get_title() == title))
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+}
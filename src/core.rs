@@ -1,32 +1,50 @@
 use std::cmp;
 use std::collections::HashMap;
+use std::mem;
 use std::mem::zeroed;
-use std::os::raw::{c_int, c_long, c_uchar, c_uint, c_ulong};
+use std::os::raw::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong};
 use std::ptr::null;
 use std::rc::Rc;
 
-use x11::xlib;
+use x11::{xlib, xrandr};
 
 use atoms;
+use libc;
 use client::{ClientL, ClientW, Rect, ClientList};
 use config::*;
 use util;
 use util::clean_mask;
-use layout::{FullScreen, Layout, Overview, Tile};
+use layout::{FullScreen, Layout, LayoutMessage, Overview};
 use loggers;
-use loggers::Logger;
+use loggers::{Logger, MonitorStatus};
+use tabbar::TabBar;
 use workspace::{FocusShift, Workspace};
 use xproto;
 
 const TRACE: bool = true;
 
-fn lookup_layout(config: Rc<Config>, tag: c_uchar) -> Box<Layout + 'static> {
+fn lookup_layouts(config: Rc<Config>, tag: c_uchar) -> Vec<Box<Layout + 'static>> {
     for &(ref t, ref l) in &config.tag_layout {
         if *t == tag {
             return l.clone();
         }
     }
-    Box::new(Tile)
+    if tag == TAG_OVERVIEW {
+        return vec![Box::new(Overview)];
+    }
+    config.layouts.clone()
+}
+
+// The center of rect.
+fn center(rect: &Rect) -> (c_int, c_int) {
+    (rect.x + rect.width / 2, rect.y + rect.height / 2)
+}
+
+// Moves rect as little as needed to fit in area, keeping to its top left corner if it is too
+// big.
+fn clamp_rect(rect: &mut Rect, area: &Rect) {
+    rect.x = cmp::max(cmp::min(rect.x, area.x + area.width - rect.width), area.x);
+    rect.y = cmp::max(cmp::min(rect.y, area.y + area.height - rect.height), area.y);
 }
 
 struct Colors {
@@ -106,6 +124,16 @@ impl BackStack {
     }
 }
 
+// A screen and the workspace it shows. The overview and union workspaces are shown on top of
+// the current monitor without taking its workspace away.
+struct Monitor {
+    // RandR output name, empty if unknown.
+    name: String,
+    primary: bool,
+    rect: Rect,
+    tag: c_uchar,
+}
+
 pub struct WindowManager {
     config: Rc<Config>,
     display: *mut xlib::Display,
@@ -114,23 +142,42 @@ pub struct WindowManager {
     root: c_ulong,
     screen_width: c_int,
     screen_height: c_int,
+    // First event number of the RandR extension, if the server has it.
+    randr_event_base: Option<c_int>,
     pub current_tag: c_uchar,
+    current_monitor: usize,
+    monitors: Vec<Monitor>,
     pub special_windows: ClientL,
     colors: Colors,
     pub workspaces: HashMap<c_uchar, Workspace>,
+    // Tags of the workspaces in the order they are listed.
+    tags: Vec<c_uchar>,
+    // Tags shown together while current_tag is TAG_UNION, the first one leading.
+    view_tags: Vec<c_uchar>,
     back_stack: BackStack,
     logger: Box<Logger + 'static>,
+    tab_bars: HashMap<c_uchar, TabBar>,
+    // The desktop last published as _NET_CURRENT_DESKTOP.
+    current_desktop: Option<c_ulong>,
 }
 
 impl WindowManager {
     pub fn new(cfg: Config) -> WindowManager {
         let config = Rc::new(cfg);
+        // Tab bars draw titles as UTF-8, which needs a locale that has the charsets for it.
+        unsafe {
+            if libc::setlocale(libc::LC_CTYPE, b"\0".as_ptr() as *const c_char).is_null() ||
+               xlib::XSupportsLocale() == 0 {
+                warn!("no locale support, tab titles may not show");
+            }
+        }
         let display = unsafe { xlib::XOpenDisplay(null()) };
         let screen = unsafe { xlib::XDefaultScreen(display) };
         let root = unsafe { xlib::XRootWindow(display, screen) };
         let width = unsafe { xlib::XDisplayWidth(display, screen) };
         let height = unsafe { xlib::XDisplayHeight(display, screen) };
-        let screen_rects = util::get_screen_rects(display);
+        let screens = util::get_screens(display);
+        let screen_rects: Vec<Rect> = screens.iter().map(|s| s.rect.clone()).collect();
         atoms::create_atoms(display);
         let mut wm = WindowManager {
             config: config.clone(),
@@ -140,12 +187,19 @@ impl WindowManager {
             root: root,
             screen_width: width,
             screen_height: height,
+            randr_event_base: None,
             current_tag: config.tag_default,
+            current_monitor: 0,
+            monitors: Vec::new(),
             special_windows: Vec::new(),
             colors: Colors::new(config.clone(), display, root),
             logger: Box::new(loggers::DummyLogger::new(loggers::LoggerConfig::default())),
             back_stack: BackStack::new(),
             workspaces: HashMap::new(),
+            tags: config.tags.clone(),
+            view_tags: Vec::new(),
+            tab_bars: HashMap::new(),
+            current_desktop: None,
         };
 
         wm.anchor_window = unsafe {
@@ -161,21 +215,15 @@ impl WindowManager {
                 wm.colors.normal_border_color,
             )
         };
-        // Add workspaces.
-        for i in 0..wm.config.tags.len() {
-            let tag = wm.config.tags[i];
-            let current_rect = screen_rects.get(i);
-            let last_rect = screen_rects.len() - 1;
+        // Add workspaces. Hidden ones take the first screen until they are shown.
+        for &tag in &wm.config.tags {
             let w = Workspace::new(
                 config.clone(),
                 wm.anchor_window,
                 tag,
                 config.get_description(tag).map(|c| c.into()),
-                lookup_layout(config.clone(), tag),
-                current_rect.map(|r| r.clone()).unwrap_or(
-                    screen_rects[last_rect]
-                        .clone(),
-                ),
+                lookup_layouts(config.clone(), tag),
+                screen_rects[0].clone(),
             );
             wm.workspaces.insert(tag, w);
         }
@@ -187,10 +235,52 @@ impl WindowManager {
                 wm.anchor_window,
                 TAG_OVERVIEW,
                 None,
-                lookup_layout(config.clone(), TAG_OVERVIEW),
+                lookup_layouts(config.clone(), TAG_OVERVIEW),
+                screen_rects[0].clone(),
+            ),
+        );
+        wm.workspaces.insert(
+            TAG_UNION,
+            Workspace::new(
+                config.clone(),
+                wm.anchor_window,
+                TAG_UNION,
+                None,
+                lookup_layouts(config.clone(), TAG_UNION),
+                screen_rects[0].clone(),
+            ),
+        );
+        wm.workspaces.insert(
+            TAG_SCRATCHPAD,
+            Workspace::new(
+                config.clone(),
+                wm.anchor_window,
+                TAG_SCRATCHPAD,
+                None,
+                vec![Box::new(FullScreen)],
                 screen_rects[0].clone(),
             ),
         );
+        wm.update_monitors(screens);
+        // Start on the primary monitor with the default tag, unless it is pinned elsewhere.
+        let home = wm.primary_monitor();
+        match wm.monitor_of(config.tag_default) {
+            Some(i) if i == home || wm.pinned_monitor(config.tag_default).is_some() ||
+                       wm.pinned_monitor(wm.monitors[home].tag).is_some() => {
+                wm.current_monitor = i;
+            }
+            Some(i) => {
+                wm.monitors[i].tag = wm.monitors[home].tag;
+                wm.monitors[home].tag = config.tag_default;
+                wm.current_monitor = home;
+            }
+            None => {
+                wm.monitors[home].tag = config.tag_default;
+                wm.current_monitor = home;
+            }
+        }
+        wm.current_tag = config.tag_default;
+        wm.update_workspace_rects();
 
         let net_atom_list = vec![
             atoms::net_active_window(),
@@ -205,6 +295,10 @@ impl WindowManager {
             atoms::net_wm_window_type(),
             atoms::net_wm_window_type_dialog(),
             atoms::net_wm_window_type_dock(),
+            atoms::net_number_of_desktops(),
+            atoms::net_current_desktop(),
+            atoms::net_desktop_names(),
+            atoms::net_wm_desktop(),
         ];
         unsafe {
             xlib::XChangeProperty(
@@ -256,7 +350,20 @@ impl WindowManager {
                 &mut xattr,
             );
             xlib::XSelectInput(display, root, xattr.event_mask);
+
+            let mut event_base: c_int = 0;
+            let mut error_base: c_int = 0;
+            if xrandr::XRRQueryExtension(display, &mut event_base, &mut error_base) != 0 {
+                xrandr::XRRSelectInput(
+                    display,
+                    root,
+                    xrandr::RRScreenChangeNotifyMask | xrandr::RRCrtcChangeNotifyMask |
+                        xrandr::RROutputChangeNotifyMask,
+                );
+                wm.randr_event_base = Some(event_base);
+            }
         }
+        wm.update_desktop_names();
         wm.grab_keys();
         wm
     }
@@ -294,24 +401,111 @@ impl WindowManager {
             xlib::XDeleteProperty(self.display, self.root, atoms::net_client_list());
         }
 
-        for (_, v) in self.workspaces.iter_mut() {
-            if v.tag == TAG_OVERVIEW {
-                continue;
+        let mut clients = self.all_clients();
+        clients.extend(self.workspaces.get(&TAG_SCRATCHPAD).unwrap().iter().cloned());
+        for c in clients {
+            unsafe {
+                xlib::XChangeProperty(
+                    self.display,
+                    self.root,
+                    atoms::net_client_list(),
+                    xlib::XA_WINDOW,
+                    32,
+                    xlib::PropModeAppend,
+                    &mut c.window() as *mut c_ulong as *mut c_uchar,
+                    1,
+                );
             }
-            for c in v.iter_mut() {
-                unsafe {
-                    xlib::XChangeProperty(
-                        self.display,
-                        self.root,
-                        atoms::net_client_list(),
-                        xlib::XA_WINDOW,
-                        32,
-                        xlib::PropModeAppend,
-                        &mut c.window() as *mut c_ulong as *mut c_uchar,
-                        1,
-                    );
-                }
+        }
+    }
+
+    // Publishes the workspaces as EWMH desktops, in the order of the workspaces, and the
+    // desktop of every client, as their indices may have shifted.
+    fn update_desktops(&mut self) {
+        self.update_desktop_names();
+        for c in self.all_clients() {
+            self.update_client_desktop(&c);
+        }
+    }
+
+    fn update_desktop_names(&mut self) {
+        let names: Vec<u8> = self.tags
+            .iter()
+            .flat_map(|t| {
+                let workspace = self.workspaces.get(t).unwrap();
+                let name = if let Some(description) = workspace.get_description() {
+                    format!("{} - {}", workspace.name, description)
+                } else {
+                    workspace.name.clone()
+                };
+                name.into_bytes().into_iter().chain(Some(0))
+            })
+            .collect();
+        let mut number = self.tags.len() as c_ulong;
+        unsafe {
+            xlib::XChangeProperty(self.display,
+                                  self.root,
+                                  atoms::net_number_of_desktops(),
+                                  xlib::XA_CARDINAL,
+                                  32,
+                                  xlib::PropModeReplace,
+                                  &mut number as *mut c_ulong as *mut c_uchar,
+                                  1);
+            xlib::XChangeProperty(self.display,
+                                  self.root,
+                                  atoms::net_desktop_names(),
+                                  atoms::utf8_string(),
+                                  8,
+                                  xlib::PropModeReplace,
+                                  names.as_ptr(),
+                                  names.len() as c_int);
+        }
+        self.update_current_desktop();
+    }
+
+    // Publishes the desktop shown on the current monitor, if it changed.
+    fn update_current_desktop(&mut self) {
+        let shown = self.current_real_tag().unwrap_or(self.monitors[self.current_monitor].tag);
+        let mut current = self.tags.iter().position(|&t| t == shown).unwrap_or(0) as c_ulong;
+        if self.current_desktop == Some(current) {
+            return;
+        }
+        self.current_desktop = Some(current);
+        unsafe {
+            xlib::XChangeProperty(self.display,
+                                  self.root,
+                                  atoms::net_current_desktop(),
+                                  xlib::XA_CARDINAL,
+                                  32,
+                                  xlib::PropModeReplace,
+                                  &mut current as *mut c_ulong as *mut c_uchar,
+                                  1);
+        }
+    }
+
+    // Publishes the desktop of client. Scratchpads put away are on none.
+    fn update_client_desktop(&self, client: &ClientW) {
+        if client.tag() == TAG_SCRATCHPAD {
+            unsafe {
+                xlib::XDeleteProperty(self.display, client.window(), atoms::net_wm_desktop());
             }
+            return;
+        }
+        // Sticky clients are on all desktops.
+        let mut desktop = if client.is_sticky() {
+            0xFFFFFFFF
+        } else {
+            self.tags.iter().position(|&t| t == client.tag()).unwrap_or(0) as c_ulong
+        };
+        unsafe {
+            xlib::XChangeProperty(self.display,
+                                  client.window(),
+                                  atoms::net_wm_desktop(),
+                                  xlib::XA_CARDINAL,
+                                  32,
+                                  xlib::PropModeReplace,
+                                  &mut desktop as *mut c_ulong as *mut c_uchar,
+                                  1);
         }
     }
 
@@ -324,47 +518,500 @@ impl WindowManager {
             if self.current_tag == tag {
                 return;
             }
+            self.move_focused_to_tag(tag);
+            self.select_tag(tag);
+        }
+    }
 
-            let current_client = {
-                let next_workspace_x = self.workspaces.get(&tag).unwrap().rect.x;
-                let workspace = self.current_workspace_mut();
-                let current_client = workspace.detach_current();
+    /// Moves the focused client to the workspace of tag, staying on the current one.
+    pub fn send_to_tag(&mut self, tag: c_uchar) {
+        if self.current_tag == TAG_OVERVIEW || self.current_tag == tag {
+            return;
+        }
+        if self.move_focused_to_tag(tag) {
+            self.arrange_windows();
+            self.do_log();
+        }
+    }
 
-                if next_workspace_x != workspace.rect.x {
-                    workspace.arrange();
+    // Takes the focused client off all its tags and puts it on tag. Returns false if there
+    // was no client to move.
+    fn move_focused_to_tag(&mut self, tag: c_uchar) -> bool {
+        if let Some(c) = self.current_workspace_mut().detach_current() {
+            self.move_client_to_tag(c, tag);
+            true
+        } else {
+            false
+        }
+    }
+
+    // Takes client off all its tags and puts it on tag alone.
+    fn move_client_to_tag(&mut self, mut client: ClientW, tag: c_uchar) {
+        for w in self.workspaces.values_mut() {
+            w.remove_client(client.clone());
+        }
+        client.set_sticky(false);
+        client.set_tag(tag);
+        self.update_client_desktop(&client);
+        self.attach(client, tag);
+    }
+
+    // Puts client on tag, and in view if tag is one of the tags viewed together.
+    fn attach(&mut self, client: ClientW, tag: c_uchar) {
+        self.workspaces.get_mut(&tag).unwrap().new_client(client.clone(), false);
+        if self.current_tag == TAG_UNION && self.view_tags.contains(&tag) {
+            let union = self.workspaces.get_mut(&TAG_UNION).unwrap();
+            if union.get_client_by_window(client.window()).is_none() {
+                union.new_client(client, false);
+            }
+        }
+    }
+
+    // Takes client off tag, and out of view if none of the tags viewed together has it.
+    fn detach(&mut self, client: ClientW, tag: c_uchar) {
+        self.workspaces.get_mut(&tag).unwrap().remove_client(client.clone());
+        if self.current_tag == TAG_UNION &&
+            !self.view_tags.iter().any(|t| {
+                self.workspaces.get(t).unwrap().get_client_by_window(client.window()).is_some()
+            })
+        {
+            self.workspaces.get_mut(&TAG_UNION).unwrap().remove_client(client);
+        }
+    }
+
+    /// The tags client is on, in the order of the workspaces.
+    pub fn client_tags(&self, client: &ClientW) -> Vec<c_uchar> {
+        self.tags
+            .iter()
+            .cloned()
+            .filter(|t| {
+                self.workspaces.get(t).map_or(false, |w| {
+                    w.get_client_by_window(client.window()).is_some()
+                })
+            })
+            .collect()
+    }
+
+    /// Puts the focused client on tag as well, or takes it off tag if it is on it and on
+    /// some other tag.
+    pub fn toggle_tag(&mut self, tag: c_uchar) {
+        if self.current_tag == TAG_OVERVIEW {
+            return;
+        }
+        if let Some(mut c) = self.current_focused() {
+            let tags = self.client_tags(&c);
+            if !tags.contains(&tag) {
+                self.attach(c.clone(), tag);
+            } else if tags.len() > 1 {
+                self.detach(c.clone(), tag);
+                if c.tag() == tag {
+                    let first = *tags.iter().find(|t| **t != tag).unwrap();
+                    c.set_tag(first);
+                    self.update_client_desktop(&c);
                 }
-                current_client
+            }
+            self.arrange_windows();
+            self.do_log();
+        }
+    }
+
+    /// Sticky clients are on all tags.
+    pub fn set_sticky(&mut self, client: ClientW, sticky: bool) {
+        client.clone().set_sticky(sticky);
+        let tags = self.tags.clone();
+        let home = client.tag();
+        for tag in tags {
+            let on_tag = self.workspaces
+                .get(&tag)
+                .unwrap()
+                .get_client_by_window(client.window())
+                .is_some();
+            if sticky && !on_tag {
+                self.attach(client.clone(), tag);
+            } else if !sticky && on_tag && tag != home {
+                self.detach(client.clone(), tag);
+            }
+        }
+        self.update_client_desktop(&client);
+    }
+
+    /// The tags of the workspaces in the order they are listed.
+    pub fn tags(&self) -> &[c_uchar] {
+        &self.tags
+    }
+
+    /// The tag of the workspace at index in the list of workspaces.
+    pub fn tag_at(&self, index: usize) -> Option<c_uchar> {
+        self.tags.get(index).cloned()
+    }
+
+    /// The tag of the workspace called name.
+    pub fn tag_named(&self, name: &str) -> Option<c_uchar> {
+        self.tags.iter().cloned().find(|t| self.workspaces.get(t).unwrap().name == name)
+    }
+
+    /// The tag of the workspace called name, which is created if there is none.
+    pub fn workspace_named(&mut self, name: &str) -> Option<c_uchar> {
+        self.tag_named(name).or_else(|| self.create_workspace(name))
+    }
+
+    /// Goes to the workspace called name, creating it if needed.
+    pub fn select_workspace(&mut self, name: &str) {
+        if let Some(tag) = self.workspace_named(name) {
+            self.select_tag(tag);
+        }
+    }
+
+    /// Goes to the workspace at index in the list of workspaces.
+    pub fn select_workspace_at(&mut self, index: usize) {
+        if let Some(tag) = self.tag_at(index) {
+            self.select_tag(tag);
+        }
+    }
+
+    // The workspace the workspace commands act on, if not in overview.
+    fn current_real_tag(&self) -> Option<c_uchar> {
+        if self.current_tag == TAG_OVERVIEW {
+            None
+        } else {
+            Some(self.viewed_tags()[0])
+        }
+    }
+
+    /// Adds an empty workspace called name after the current one and returns its tag. Gives
+    /// None if the name is taken or there is no tag left for it.
+    pub fn create_workspace(&mut self, name: &str) -> Option<c_uchar> {
+        if self.tag_named(name).is_some() {
+            return None;
+        }
+        let tag = match (TAG_SCRATCHPAD as u32 + 1..256)
+            .map(|t| t as c_uchar)
+            .find(|t| !self.workspaces.contains_key(t)) {
+            Some(tag) => tag,
+            None => return None,
+        };
+        let rect = self.current_workspace().rect.clone();
+        let mut workspace = Workspace::new(self.config.clone(),
+                                           self.anchor_window,
+                                           tag,
+                                           None,
+                                           self.config.layouts.clone(),
+                                           rect);
+        workspace.name = name.to_string();
+        self.workspaces.insert(tag, workspace);
+        let position = self.current_real_tag()
+            .and_then(|t| self.tags.iter().position(|x| *x == t))
+            .map_or(self.tags.len(), |p| p + 1);
+        self.tags.insert(position, tag);
+        // Sticky clients are on all workspaces, new ones included.
+        for c in self.all_clients() {
+            if c.is_sticky() {
+                self.workspaces.get_mut(&tag).unwrap().new_client(c, true);
+            }
+        }
+        self.update_desktops();
+        self.do_log();
+        Some(tag)
+    }
+
+    /// Renames the current workspace. Returns false if another workspace has that name.
+    pub fn rename_workspace(&mut self, name: &str) -> bool {
+        match self.tag_named(name) {
+            Some(t) if Some(t) != self.current_real_tag() => return false,
+            _ => (),
+        }
+        if let Some(tag) = self.current_real_tag() {
+            self.workspaces.get_mut(&tag).unwrap().name = name.to_string();
+            self.update_desktop_names();
+            self.do_log();
+        }
+        true
+    }
+
+    /// Moves the current workspace delta places up or down the list of workspaces.
+    pub fn move_workspace(&mut self, delta: i32) {
+        if let Some(tag) = self.current_real_tag() {
+            let position = self.tags.iter().position(|t| *t == tag).unwrap();
+            let target = cmp::max(0, cmp::min(self.tags.len() as i32 - 1, position as i32 + delta));
+            let tag = self.tags.remove(position);
+            self.tags.insert(target as usize, tag);
+            self.update_desktops();
+            self.do_log();
+        }
+    }
+
+    /// Deletes the current workspace and goes to its neighbour. Clients that are on no other
+    /// workspace move to the neighbour as well. Nothing happens if every other workspace is
+    /// on a monitor already.
+    pub fn delete_workspace(&mut self) {
+        if let Some(tag) = self.current_real_tag() {
+            // The monitor needs another workspace to show, one that no monitor shows yet.
+            let position = self.tags.iter().position(|t| *t == tag).unwrap();
+            let n = self.tags.len();
+            let neighbour = match (1..n)
+                .map(|i| self.tags[(position + i) % n])
+                .find(|t| self.monitor_of(*t).is_none()) {
+                Some(t) => t,
+                None => return,
             };
-            if let Some(mut c) = current_client {
-                let workspace = self.workspaces.get_mut(&tag).unwrap();
-                c.set_tag(tag);
-                workspace.new_client(c, false);
+            if self.current_tag == TAG_UNION {
+                self.toggle_view(tag);
+            } else {
+                self.select_tag(neighbour);
+            }
+            if let Some(i) = self.monitor_of(tag) {
+                self.monitors[i].tag = neighbour;
+                self.update_workspace_rects();
+            }
+            // Switching away may have removed the workspace already if it was empty.
+            let clients: Vec<ClientW> = match self.workspaces.get(&tag) {
+                Some(w) => w.iter().cloned().collect(),
+                None => Vec::new(),
+            };
+            let home = self.current_real_tag().unwrap();
+            self.remove_workspace(tag);
+            for mut c in clients {
+                if self.client_tags(&c).is_empty() {
+                    c.set_tag(home);
+                    self.attach(c, home);
+                }
+            }
+            self.update_desktops();
+            self.arrange_windows();
+            self.do_log();
+        }
+    }
+
+    // Drops the workspace of tag. Its clients that are on other workspaces get one of them
+    // as their tag; the others are left for the caller to place.
+    fn remove_workspace(&mut self, tag: c_uchar) {
+        self.tags.retain(|t| *t != tag);
+        self.tab_bars.remove(&tag).map(|mut t| t.hide());
+        if let Some(w) = self.workspaces.remove(&tag) {
+            for mut c in w.iter().cloned() {
+                if c.tag() == tag {
+                    if let Some(&t) = self.client_tags(&c).first() {
+                        c.set_tag(t);
+                    }
+                }
+            }
+        }
+    }
+
+    // With Config::remove_empty_workspaces, drops the workspaces that are out of view and
+    // hold no clients but sticky ones.
+    fn remove_empty_workspaces(&mut self) {
+        if !self.config.remove_empty_workspaces {
+            return;
+        }
+        let viewed = self.viewed_tags();
+        let count = self.tags.len();
+        for tag in self.tags.clone() {
+            let empty = self.workspaces.get(&tag).unwrap().iter().all(|c| c.is_sticky());
+            if empty && !viewed.contains(&tag) && self.monitor_of(tag).is_none() {
+                self.remove_workspace(tag);
+            }
+        }
+        if self.tags.len() != count {
+            self.update_desktops();
+        }
+    }
+
+    /// Shows the scratchpad called name on the current workspace, or puts it away if it is
+    /// there already. Starts the program of the scratchpad if it is not running.
+    pub fn toggle_scratchpad(&mut self, name: &str) {
+        if self.current_tag == TAG_OVERVIEW {
+            return;
+        }
+        let config = self.config.clone();
+        let scratchpad = match config.scratchpads.iter().find(|s| s.name == name) {
+            Some(s) => s,
+            None => return,
+        };
+        match self.scratchpad_client(name) {
+            Some(c) => {
+                if self.is_on_screen(&c) {
+                    self.stash_scratchpad(c);
+                } else {
+                    self.summon_scratchpad(c, scratchpad.size);
+                }
             }
+            None => {
+                let args: Vec<&str> = scratchpad.args.iter().map(|a| a.as_str()).collect();
+                util::spawn(&scratchpad.command, &args);
+            }
+        }
+    }
+
+    // The window of the scratchpad called name, shown or not.
+    fn scratchpad_client(&self, name: &str) -> Option<ClientW> {
+        for w in self.workspaces.values() {
+            if let Some(c) = w.iter().find(|c| {
+                c.get_extra("scratchpad").map_or(false, |n| n.as_str() == name)
+            })
+            {
+                return Some(c.clone());
+            }
+        }
+        None
+    }
+
+    // Floats client in the middle of the current screen.
+    fn place_scratchpad(&self, client: &mut ClientW, size: f32) {
+        let rect = self.current_workspace().rect.clone();
+        let width = (rect.width as f32 * size) as c_int;
+        let height = (rect.height as f32 * size) as c_int;
+        client.set_floating(true);
+        client.set_size(rect.x + (rect.width - width) / 2,
+                        rect.y + (rect.height - height) / 2,
+                        width,
+                        height);
+    }
+
+    fn summon_scratchpad(&mut self, mut client: ClientW, size: f32) {
+        let tag = self.viewed_tags()[0];
+        for w in self.workspaces.values_mut() {
+            w.remove_client(client.clone());
+        }
+        client.set_tag(tag);
+        self.update_client_desktop(&client);
+        self.place_scratchpad(&mut client, size);
+        self.attach(client.clone(), tag);
+        self.arrange_windows();
+        self.set_focus(client);
+        self.do_log();
+    }
+
+    fn stash_scratchpad(&mut self, mut client: ClientW) {
+        for w in self.workspaces.values_mut() {
+            w.remove_client(client.clone());
+        }
+        self.back_stack.remove(client.clone());
+        client.set_sticky(false);
+        client.set_tag(TAG_SCRATCHPAD);
+        self.update_client_desktop(&client);
+        self.workspaces.get_mut(&TAG_SCRATCHPAD).unwrap().new_client(client.clone(), false);
+        client.show(false);
+        self.arrange_windows();
+        match self.current_focused() {
+            Some(c) => self.set_focus(c),
+            None => unsafe {
+                xlib::XSetInputFocus(self.display,
+                                     self.root,
+                                     xlib::RevertToPointerRoot,
+                                     xlib::CurrentTime);
+            },
+        }
+        self.do_log();
+    }
+
+    /// The tags on screen: the current tag, or all the tags viewed together.
+    pub fn viewed_tags(&self) -> Vec<c_uchar> {
+        if self.current_tag == TAG_UNION {
+            self.view_tags.clone()
+        } else {
+            vec![self.current_tag]
+        }
+    }
+
+    /// Shows the clients of tag along with the ones already on screen, or stops showing
+    /// them if tag is already on screen.
+    pub fn toggle_view(&mut self, tag: c_uchar) {
+        if self.current_tag == TAG_OVERVIEW {
             self.select_tag(tag);
+            return;
+        }
+        let mut tags = self.viewed_tags();
+        if let Some(position) = tags.iter().position(|t| *t == tag) {
+            tags.remove(position);
+        } else {
+            tags.push(tag);
+        }
+        match tags.len() {
+            0 => (),
+            1 => self.select_tag(tags[0]),
+            _ => self.view(tags),
         }
     }
 
+    // Shows the clients of several tags at once, laid out in the union workspace.
+    fn view(&mut self, tags: Vec<c_uchar>) {
+        let lead = tags[0];
+        let rect = self.monitors[self.current_monitor].rect.clone();
+        if self.current_tag != TAG_UNION || self.view_tags[0] != lead {
+            // The layouts follow the leading tag.
+            let union = Workspace::new(self.config.clone(),
+                                       self.anchor_window,
+                                       TAG_UNION,
+                                       None,
+                                       lookup_layouts(self.config.clone(), lead),
+                                       rect);
+            self.workspaces.insert(TAG_UNION, union);
+        }
+        let mut clients: Vec<ClientW> = Vec::new();
+        for t in &tags {
+            for c in self.workspaces.get(t).unwrap().iter() {
+                if !clients.iter().any(|x| x.window() == c.window()) {
+                    clients.push(c.clone());
+                }
+            }
+        }
+        {
+            let union = self.workspaces.get_mut(&TAG_UNION).unwrap();
+            union.clear();
+            for c in clients.into_iter().rev() {
+                union.new_client(c, false);
+            }
+        }
+        self.view_tags = tags;
+        self.switch_tag(TAG_UNION);
+    }
+
     pub fn select_tag(&mut self, tag: c_uchar) {
-        if tag == self.current_tag {
+        if tag == self.current_tag || tag == TAG_UNION || tag == TAG_SCRATCHPAD {
             return;
         }
+        if self.current_tag == TAG_UNION {
+            self.workspaces.get_mut(&TAG_UNION).unwrap().clear();
+            self.view_tags.clear();
+        }
+        self.switch_tag(tag);
+    }
 
+    // Shows tag on the current monitor. A tag that is shown on another monitor trades places
+    // with the one on the current monitor, unless the overview or union workspace is in front
+    // or the current workspace is pinned, in which case the other monitor becomes the current
+    // one. Pinned tags are shown on their own monitor.
+    fn switch_tag(&mut self, tag: c_uchar) {
         let old_client = self.current_focused();
-
-        if tag == TAG_OVERVIEW {
-            self.current_tag = tag;
+        if self.is_pseudo_tag(tag) {
+            let rect = self.monitors[self.current_monitor].rect.clone();
+            self.workspaces.get_mut(&tag).unwrap().update_rect(rect);
         } else {
-            let mut sticky_clients = self.current_workspace().select_clients(&|c| c.is_sticky());
-            for c in sticky_clients.iter_mut() {
-                self.current_workspace_mut().remove_client(c.clone());
-            }
-            self.current_tag = tag;
-            debug!("[select_tag] self.current_tag={}", self.current_tag);
-            for c in sticky_clients.iter_mut() {
-                c.set_tag(tag);
-                self.current_workspace_mut().new_client(c.clone(), true);
+            let current_pinned = self.pinned_monitor(self.monitors[self.current_monitor].tag) ==
+                Some(self.current_monitor);
+            if let Some(i) = self.pinned_monitor(tag) {
+                // Workspaces pinned to a monitor do not leave it.
+                self.current_monitor = i;
+                self.monitors[i].tag = tag;
+            } else {
+                match self.monitor_of(tag) {
+                    Some(i) if i != self.current_monitor &&
+                                   (current_pinned || self.is_pseudo_tag(self.current_tag)) => {
+                        self.current_monitor = i;
+                    }
+                    Some(i) => {
+                        let current_tag = self.monitors[self.current_monitor].tag;
+                        self.monitors[i].tag = current_tag;
+                        self.monitors[self.current_monitor].tag = tag;
+                    }
+                    None => self.monitors[self.current_monitor].tag = tag,
+                }
             }
+            self.update_workspace_rects();
         }
+        self.current_tag = tag;
+        debug!("[select_tag] self.current_tag={}", self.current_tag);
         unsafe {
             xlib::XSetInputFocus(
                 self.display,
@@ -397,9 +1044,277 @@ impl WindowManager {
 
     pub fn toggle_back(&mut self) {
         if let Some(c) = self.back_stack.pop() {
-            self.select_tag(c.tag());
+            self.show_client(&c);
+            self.set_focus(c);
+        }
+    }
+
+    /// Brings client into view: moves to the monitor that shows one of its workspaces, or
+    /// else shows its workspace on the current monitor.
+    pub fn show_client(&mut self, client: &ClientW) {
+        if self.is_on_screen(client) || client.tag() == TAG_SCRATCHPAD {
+            return;
+        }
+        let monitor = self.client_tags(client)
+            .into_iter()
+            .filter_map(|t| self.monitor_of(t))
+            .next();
+        match monitor {
+            Some(i) => self.select_monitor(i),
+            None => self.select_tag(client.tag()),
+        }
+    }
+
+    // The monitor showing the workspace of tag.
+    fn monitor_of(&self, tag: c_uchar) -> Option<usize> {
+        self.monitors.iter().position(|m| m.tag == tag)
+    }
+
+    fn is_pseudo_tag(&self, tag: c_uchar) -> bool {
+        tag == TAG_OVERVIEW || tag == TAG_UNION
+    }
+
+    // Makes monitor i the current one. The overview or union workspace goes away if it
+    // was in front of the current monitor.
+    fn select_monitor(&mut self, i: usize) {
+        if i == self.current_monitor {
+            return;
+        }
+        let was_pseudo = self.is_pseudo_tag(self.current_tag);
+        if self.current_tag == TAG_UNION {
+            self.workspaces.get_mut(&TAG_UNION).unwrap().clear();
+            self.view_tags.clear();
+        }
+        self.current_monitor = i;
+        self.current_tag = self.monitors[i].tag;
+        if was_pseudo {
+            self.arrange_windows();
+        }
+        self.do_log();
+    }
+
+    /// Moves focus to the monitor delta places after the current one.
+    pub fn focus_monitor(&mut self, delta: i32) {
+        let n = self.monitors.len() as i32;
+        let i = ((self.current_monitor as i32 + delta) % n + n) % n;
+        self.select_monitor(i as usize);
+        match self.current_focused() {
+            Some(c) => self.set_focus(c),
+            None => unsafe {
+                xlib::XSetInputFocus(self.display,
+                                     self.root,
+                                     xlib::RevertToPointerRoot,
+                                     xlib::CurrentTime);
+            },
+        }
+    }
+
+    /// Sends the focused client to the workspace of the monitor delta places after the
+    /// current one. Floating clients keep their place relative to the monitor.
+    pub fn send_to_monitor(&mut self, delta: i32) {
+        let n = self.monitors.len() as i32;
+        if self.current_tag == TAG_OVERVIEW || n < 2 {
+            return;
+        }
+        let i = (((self.current_monitor as i32 + delta) % n + n) % n) as usize;
+        if let Some(mut c) = self.current_focused() {
+            if c.is_floating() {
+                let from = self.monitors[self.current_monitor].rect.clone();
+                let to = self.monitors[i].rect.clone();
+                let rect = c.get_rect();
+                let x = cmp::min(to.x + rect.x - from.x, to.x + to.width - rect.width);
+                let y = cmp::min(to.y + rect.y - from.y, to.y + to.height - rect.height);
+                c.set_size(cmp::max(x, to.x), cmp::max(y, to.y), rect.width, rect.height);
+            }
+        }
+        let tag = self.monitors[i].tag;
+        if self.move_focused_to_tag(tag) {
+            self.arrange_windows();
+            if let Some(c) = self.current_focused() {
+                self.set_focus(c);
+            }
+            self.do_log();
+        }
+    }
+
+    // The monitor at point, if any.
+    fn monitor_at(&self, (x, y): (c_int, c_int)) -> Option<usize> {
+        self.monitors.iter().position(|m| {
+            x >= m.rect.x && x < m.rect.x + m.rect.width && y >= m.rect.y &&
+            y < m.rect.y + m.rect.height
+        })
+    }
+
+    // The smallest rect that covers all the monitors.
+    fn screens_rect(&self) -> Rect {
+        let left = self.monitors.iter().map(|m| m.rect.x).min().unwrap_or(0);
+        let top = self.monitors.iter().map(|m| m.rect.y).min().unwrap_or(0);
+        let right = self.monitors.iter().map(|m| m.rect.x + m.rect.width).max().unwrap_or(0);
+        let bottom = self.monitors.iter().map(|m| m.rect.y + m.rect.height).max().unwrap_or(0);
+        Rect::new(left, top, right - left, bottom - top)
+    }
+
+    // The rect of the monitor under the center of rect, or of the current one if there is
+    // none.
+    fn monitor_rect_at(&self, rect: &Rect) -> Rect {
+        self.monitor_at(center(rect))
+            .map(|i| self.monitors[i].rect.clone())
+            .unwrap_or_else(|| self.current_workspace().rect.clone())
+    }
+
+    // Moves the rect of a floating window anywhere on the monitors, but not into the dead
+    // areas between monitors of different sizes.
+    fn clamp_to_screens(&self, rect: Rect) -> Rect {
+        let mut rect = rect;
+        clamp_rect(&mut rect, &self.screens_rect());
+        if self.monitor_at(center(&rect)).is_none() {
+            clamp_rect(&mut rect, &self.current_workspace().rect);
+        }
+        rect
+    }
+
+    // Hands a floating client whose center went over to another monitor to the workspace
+    // of that monitor, and follows it there.
+    fn follow_to_monitor(&mut self, client: &ClientW) {
+        if !client.is_floating() || client.is_sticky() || self.is_pseudo_tag(self.current_tag) {
+            return;
+        }
+        let i = match self.monitor_at(center(&client.get_rect())) {
+            Some(i) if i != self.current_monitor => i,
+            _ => return,
+        };
+        match self.current_focused() {
+            Some(ref c) if c.window() == client.window() => (),
+            _ => return,
+        }
+        let tag = self.monitors[i].tag;
+        if self.move_focused_to_tag(tag) {
+            self.select_monitor(i);
+            self.arrange_windows();
+            self.set_focus(client.clone());
+            self.do_log();
+        }
+    }
+
+    // Catches up with monitors that were added, removed or resized. Workspaces of monitors
+    // that are gone are hidden with their windows, to be shown again on demand.
+    fn update_screens(&mut self) {
+        let screens = util::get_screens(self.display);
+        let unchanged = screens.len() == self.monitors.len() &&
+            self.monitors.iter().zip(screens.iter()).all(|(m, s)| {
+                m.rect == s.rect && m.name == s.name && m.primary == s.primary
+            });
+        if unchanged {
+            return;
+        }
+        debug!("screens changed: {:?}", screens);
+        self.update_monitors(screens);
+        self.arrange_windows();
+        if let Some(c) = self.current_focused() {
             self.set_focus(c);
         }
+        self.do_log();
+    }
+
+    // Sets up a monitor for each screen. A monitor shows the workspace pinned to its output,
+    // else the workspace it showed before, else a hidden one. Monitors are told apart by
+    // output name, or by position when RandR does not name them.
+    fn update_monitors(&mut self, screens: Vec<util::Screen>) {
+        let old = mem::replace(&mut self.monitors, Vec::new());
+        let current_name = old.get(self.current_monitor).map(|m| m.name.clone());
+        let mut tags: Vec<Option<c_uchar>> = screens.iter()
+            .map(|s| {
+                self.tags.iter().cloned().find(|t| {
+                    !s.name.is_empty() && self.config.get_output(*t) == Some(s.name.as_str())
+                })
+            })
+            .collect();
+        for (i, s) in screens.iter().enumerate() {
+            if tags[i].is_some() {
+                continue;
+            }
+            let previous = old.iter()
+                .enumerate()
+                .find(|&(j, m)| if s.name.is_empty() { i == j } else { m.name == s.name })
+                .map(|(_, m)| m.tag);
+            if let Some(tag) = previous {
+                if !tags.contains(&Some(tag)) && self.workspaces.contains_key(&tag) {
+                    tags[i] = Some(tag);
+                }
+            }
+        }
+        for i in 0..screens.len() {
+            if tags[i].is_none() {
+                // Prefer workspaces that are not waiting for an output of their own.
+                let hidden = {
+                    let is_free = |t: &c_uchar| !tags.contains(&Some(*t));
+                    let is_pinned_elsewhere = |t: &c_uchar| {
+                        self.config
+                            .get_output(*t)
+                            .map_or(false, |o| screens.iter().any(|s| s.name == o))
+                    };
+                    self.tags
+                        .iter()
+                        .cloned()
+                        .find(|t| is_free(t) && !is_pinned_elsewhere(t))
+                        .or_else(|| self.tags.iter().cloned().find(|t| is_free(t)))
+                };
+                tags[i] = hidden;
+            }
+        }
+        for (s, tag) in screens.into_iter().zip(tags.into_iter()) {
+            // With more monitors than workspaces, the rest stay blank.
+            if let Some(tag) = tag {
+                self.monitors.push(Monitor {
+                    name: s.name,
+                    primary: s.primary,
+                    rect: s.rect,
+                    tag: tag,
+                });
+            }
+        }
+        self.current_monitor = current_name
+            .and_then(|n| self.monitors.iter().position(|m| m.name == n))
+            .unwrap_or(self.primary_monitor());
+        if !self.is_pseudo_tag(self.current_tag) {
+            self.current_tag = self.monitors[self.current_monitor].tag;
+        }
+        self.update_workspace_rects();
+    }
+
+    // The primary monitor, or the first one if RandR does not name one.
+    fn primary_monitor(&self) -> usize {
+        self.monitors.iter().position(|m| m.primary).unwrap_or(0)
+    }
+
+    // The monitor showing the workspace of tag whenever the output it is pinned to is there.
+    fn pinned_monitor(&self, tag: c_uchar) -> Option<usize> {
+        self.config
+            .get_output(tag)
+            .and_then(|o| self.monitors.iter().position(|m| m.name == o))
+    }
+
+    // Whether the monitor leaves room for a bar: all of them do unless the config names some.
+    fn has_bar(&self, monitor: usize) -> bool {
+        self.config.bar_outputs.is_empty() ||
+        self.config.bar_outputs.contains(&self.monitors[monitor].name)
+    }
+
+    // Gives the workspaces on monitors the rects of their monitors.
+    fn update_workspace_rects(&mut self) {
+        let bars: Vec<bool> = (0..self.monitors.len()).map(|i| self.has_bar(i)).collect();
+        for (i, m) in self.monitors.iter().enumerate() {
+            let w = self.workspaces.get_mut(&m.tag).unwrap();
+            w.update_rect(m.rect.clone());
+            w.has_bar = bars[i];
+        }
+        let rect = self.monitors[self.current_monitor].rect.clone();
+        let has_bar = bars[self.current_monitor];
+        for tag in &[TAG_OVERVIEW, TAG_UNION] {
+            let w = self.workspaces.get_mut(tag).unwrap();
+            w.update_rect(rect.clone());
+            w.has_bar = has_bar;
+        }
     }
 
     pub fn toggle_maximize(&mut self) {
@@ -412,6 +1327,20 @@ impl WindowManager {
         }
     }
 
+    /// Changes the weight of the focused tiled client, making it bigger or smaller than
+    /// its neighbours in layouts that size clients by weight.
+    pub fn inc_weight(&mut self, delta: i32) {
+        if self.current_tag != TAG_OVERVIEW {
+            if let Some(mut c) = self.current_focused() {
+                if !c.is_floating() {
+                    let weight = c.weight();
+                    c.set_weight(weight + delta);
+                    self.arrange_windows();
+                }
+            }
+        }
+    }
+
     pub fn toggle_floating(&mut self) {
         if let Some(mut c) = self.current_focused() {
             let floating = c.is_floating();
@@ -446,15 +1375,21 @@ impl WindowManager {
     }
 
     pub fn set_focus(&mut self, client: ClientW) {
+        if client.tag() == TAG_SCRATCHPAD {
+            return;
+        }
         {
             if self.current_tag != TAG_OVERVIEW {
-                debug!("selecting tag: {}", client.tag() as char);
-                self.select_tag(client.tag());
+                self.show_client(&client);
             }
             let workspace = self.current_workspace_mut();
             workspace.set_focus(client.clone());
+            if workspace.current_layout().depends_on_focus() {
+                workspace.arrange();
+            }
             workspace.restack();
         }
+        self.refresh_tab_bar();
 
         unsafe {
             let mut window = client.window();
@@ -482,6 +1417,7 @@ impl WindowManager {
                 FocusShift::Backward
             });
         }
+        self.refresh_tab_bar();
         self.do_log();
     }
 
@@ -493,31 +1429,22 @@ impl WindowManager {
         self.arrange_windows();
     }
 
+    /// Moves the focused floating client by delta_x and delta_y, across monitors if need be.
     pub fn shift_window(&mut self, delta_x: c_int, delta_y: c_int) {
         if let Some(mut client) = self.current_focused() {
             if !client.is_floating() {
                 return;
             }
-            let screen_rect = self.current_workspace().rect.clone();
             let rect = client.get_rect();
-            let mut target_x = rect.x + delta_x;
-            let mut target_y = rect.y + delta_y;
-            if target_x < screen_rect.x {
-                target_x = screen_rect.x;
-            }
-            if target_x > screen_rect.width - rect.width {
-                target_x = screen_rect.width - rect.width;
-            }
-            if target_y < screen_rect.y {
-                target_y = screen_rect.y;
-            }
-            if target_y > screen_rect.height - rect.height {
-                target_y = screen_rect.height - rect.height;
-            }
-            client.move_window(target_x, target_y, true);
+            let target = self.clamp_to_screens(Rect::new(rect.x + delta_x,
+                                                         rect.y + delta_y,
+                                                         rect.width,
+                                                         rect.height));
+            client.move_window(target.x, target.y, true);
             unsafe {
                 xlib::XSync(self.display, 0);
             }
+            self.follow_to_monitor(&client);
         }
     }
 
@@ -526,12 +1453,14 @@ impl WindowManager {
             if !client.is_floating() {
                 return;
             }
-            let screen_rect = self.current_workspace().rect.clone();
             let mut rect = client.get_rect();
+            let screen_rect = self.monitor_rect_at(&rect);
             rect.width = delta + rect.width;
 
-            if rect.width > screen_rect.width {
-                rect.width = screen_rect.width;
+            let max_width = screen_rect.x + screen_rect.width - 2 * self.config.border_width -
+                            rect.x;
+            if rect.width > max_width {
+                rect.width = max_width;
             }
             if rect.width < 10 {
                 return;
@@ -545,12 +1474,14 @@ impl WindowManager {
             if !client.is_floating() {
                 return;
             }
-            let screen_rect = self.current_workspace().rect.clone();
             let mut rect = client.get_rect();
+            let screen_rect = self.monitor_rect_at(&rect);
             rect.height = delta + rect.height;
 
-            if rect.height > screen_rect.height {
-                rect.height = screen_rect.height;
+            let max_height = screen_rect.y + screen_rect.height - 2 * self.config.border_width -
+                             rect.y;
+            if rect.height > max_height {
+                rect.height = max_height;
             }
 
             if rect.height < 10 {
@@ -561,6 +1492,45 @@ impl WindowManager {
         }
     }
 
+    pub fn send_layout_message(&mut self, message: LayoutMessage) {
+        if self.current_workspace_mut().send_layout_message(&message) {
+            self.arrange_windows();
+        }
+    }
+
+    pub fn resize_split(&mut self, delta: f32) {
+        if let Some(c) = self.current_focused() {
+            self.send_layout_message(LayoutMessage::ResizeSplit(c.window(), delta));
+        }
+    }
+
+    pub fn inc_gaps(&mut self, delta: c_int) {
+        self.send_layout_message(LayoutMessage::IncGaps(delta));
+    }
+
+    pub fn toggle_gaps(&mut self) {
+        self.send_layout_message(LayoutMessage::ToggleGaps);
+    }
+
+    pub fn next_layout(&mut self) {
+        self.current_workspace_mut().next_layout();
+        self.arrange_windows();
+        self.do_log();
+    }
+
+    pub fn prev_layout(&mut self) {
+        self.current_workspace_mut().prev_layout();
+        self.arrange_windows();
+        self.do_log();
+    }
+
+    pub fn select_layout(&mut self, name: &str) {
+        if self.current_workspace_mut().select_layout(name) {
+            self.arrange_windows();
+            self.do_log();
+        }
+    }
+
     pub fn zoom(&mut self) {
         {
             let workspace = {
@@ -584,16 +1554,38 @@ impl WindowManager {
     }
 
     pub fn all_clients(&self) -> Vec<ClientW> {
-        let mut result = Vec::new();
+        let mut result: Vec<ClientW> = Vec::new();
         for (_, w) in &self.workspaces {
-            if w.tag == TAG_OVERVIEW {
+            if w.tag == TAG_OVERVIEW || w.tag == TAG_UNION || w.tag == TAG_SCRATCHPAD {
                 continue;
             }
-            result.extend(w.iter().cloned());
+            // Clients on several tags are listed once.
+            for c in w.iter() {
+                if !result.iter().any(|r| r.window() == c.window()) {
+                    result.push(c.clone());
+                }
+            }
         }
         result
     }
 
+    // Whether client is in the workspace on screen.
+    fn is_on_screen(&self, client: &ClientW) -> bool {
+        self.current_workspace().get_client_by_window(client.window()).is_some()
+    }
+
+    // Whether client is in the workspace on screen or in one on another monitor.
+    fn is_shown(&self, client: &ClientW) -> bool {
+        if self.is_on_screen(client) {
+            return true;
+        }
+        // The overview takes the windows off the other monitors.
+        self.current_tag != TAG_OVERVIEW &&
+        self.monitors.iter().any(|m| {
+            self.workspaces.get(&m.tag).unwrap().get_client_by_window(client.window()).is_some()
+        })
+    }
+
     pub fn current_clients(&self) -> Vec<ClientW> {
         self.current_workspace().iter().cloned().collect()
     }
@@ -662,6 +1654,14 @@ impl WindowManager {
             unsafe {
                 xlib::XUngrabPointer(self.display, xlib::CurrentTime);
             }
+            if client.is_floating() {
+                let rect = client.get_rect();
+                let target = self.clamp_to_screens(rect.clone());
+                if target != rect {
+                    client.move_window(target.x, target.y, true);
+                }
+            }
+            self.follow_to_monitor(client);
         }
     }
 
@@ -671,6 +1671,11 @@ impl WindowManager {
         }
 
         let rect = client.get_rect();
+        // The window grows no further than the monitor it is on.
+        let screen_rect = self.monitor_rect_at(&rect);
+        let max_width = screen_rect.x + screen_rect.width - 2 * self.config.border_width - rect.x;
+        let max_height = screen_rect.y + screen_rect.height - 2 * self.config.border_width -
+                         rect.y;
         let mouse_mask = xlib::ButtonPressMask | xlib::ButtonReleaseMask | xlib::PointerMotionMask;
         if unsafe {
             xlib::XGrabPointer(
@@ -715,8 +1720,10 @@ impl WindowManager {
                                 continue;
                             }
                             last_time = me.time;
-                            let nw = cmp::max(me.x - 2 * self.config.border_width - rect.x + 1, 1);
-                            let nh = cmp::max(me.y - 2 * self.config.border_width - rect.y + 1, 1);
+                            let nw = me.x - 2 * self.config.border_width - rect.x + 1;
+                            let nh = me.y - 2 * self.config.border_width - rect.y + 1;
+                            let nw = cmp::max(cmp::min(nw, max_width), 1);
+                            let nh = cmp::max(cmp::min(nh, max_height), 1);
                             if client.is_floating() {
                                 client.resize(Rect::new(rect.x, rect.y, nw, nh), false);
                             }
@@ -746,7 +1753,13 @@ impl WindowManager {
 
     fn manage_window(&mut self, window: c_ulong, xa: &xlib::XWindowAttributes) {
         let tag = if self.current_tag == TAG_OVERVIEW {
-            self.config.tag_default
+            if self.workspaces.contains_key(&self.config.tag_default) {
+                self.config.tag_default
+            } else {
+                self.tags[0]
+            }
+        } else if self.current_tag == TAG_UNION {
+            self.view_tags[0]
         } else {
             self.current_tag
         };
@@ -783,16 +1796,11 @@ impl WindowManager {
                 &client.window() as *const c_ulong as *const u8,
                 1,
             );
-            let mut wc: xlib::XWindowChanges = zeroed();
-            wc.border_width = self.config.border_width;
             self.update_window_type(client.clone());
             if !(client.is_dock()) {
-                xlib::XConfigureWindow(
-                    self.display,
-                    window,
-                    xlib::CWBorderWidth as c_uint,
-                    &mut wc,
-                );
+                if !client.is_fullscreen() {
+                    client.set_border_width(self.config.border_width);
+                }
                 xlib::XSetWindowBorder(self.display, window, self.colors.normal_border_color);
                 client.configure();
             }
@@ -813,6 +1821,15 @@ impl WindowManager {
             }
         }
 
+        let config = self.config.clone();
+        if let Some(s) = config.scratchpads.iter().find(|s| {
+            (s.matcher)(&client) && self.scratchpad_client(&s.name).is_none()
+        })
+        {
+            client.put_extra("scratchpad".to_string(), s.name.clone());
+            self.place_scratchpad(&mut client, s.size);
+        }
+
         if client.is_dock() {
             self.special_windows.push(client.clone());
         } else {
@@ -820,6 +1837,15 @@ impl WindowManager {
                 let workspace = self.workspaces.get_mut(&tag).unwrap();
                 workspace.new_client(client.clone(), client.is_floating());
             }
+            if self.current_tag == TAG_UNION {
+                let union = self.workspaces.get_mut(&TAG_UNION).unwrap();
+                union.new_client(client.clone(), client.is_floating());
+            }
+            if client.is_sticky() {
+                self.set_sticky(client.clone(), true);
+            } else {
+                self.update_client_desktop(&client);
+            }
             self.arrange_windows();
         }
     }
@@ -832,13 +1858,10 @@ impl WindowManager {
                 });
             }
             {
-                if self.current_tag == TAG_OVERVIEW {
-                    let real_workspace = self.workspaces.get_mut(&c.tag()).unwrap();
-                    real_workspace.remove_client(c.clone());
-                }
                 self.back_stack.remove(c.clone());
-                let workspace = self.workspaces.get_mut(&c.tag()).unwrap();
-                workspace.remove_client(c);
+                for w in self.workspaces.values_mut() {
+                    w.remove_client(c.clone());
+                }
             }
             self.update_client_list();
             self.arrange_windows();
@@ -847,30 +1870,64 @@ impl WindowManager {
 
     fn do_log(&mut self) {
         let all_clients = self.all_clients();
-        let current_clients = self.current_clients();
-        let current_focused = self.current_focused();
+        let monitors: Vec<MonitorStatus> = (0..self.monitors.len())
+            .map(|i| {
+                let (tags, workspace) = if i == self.current_monitor {
+                    (self.viewed_tags(), self.current_workspace())
+                } else {
+                    let tag = self.monitors[i].tag;
+                    (vec![tag], self.workspaces.get(&tag).unwrap())
+                };
+                MonitorStatus {
+                    name: self.monitors[i].name.clone(),
+                    rect: self.monitors[i].rect.clone(),
+                    has_bar: self.has_bar(i),
+                    current: i == self.current_monitor,
+                    tags: tags,
+                    layout_name: workspace.get_layout_name().to_string(),
+                    clients: workspace.iter().cloned().collect(),
+                    focused: workspace.get_current_focused(),
+                }
+            })
+            .collect();
         self.logger.dump(
             &self.config,
             &self.workspaces,
+            &self.tags,
             &all_clients,
-            self.current_tag,
-            &current_clients,
-            current_focused,
+            &monitors,
         );
+        self.update_current_desktop();
     }
 
     pub fn arrange_windows(&mut self) {
-        // TODO: Also arrange windows in the workspace that lose focus.
-        let screen_rect = self.current_workspace().rect.clone();
+        self.remove_empty_workspaces();
+        let shown: Vec<c_uchar> = (0..self.monitors.len())
+            .map(|i| if i == self.current_monitor {
+                self.current_tag
+            } else {
+                self.monitors[i].tag
+            })
+            .collect();
         for (_, mut w) in self.workspaces.iter_mut() {
             let tag = w.tag;
             if tag == TAG_OVERVIEW {
                 continue;
             }
-            if tag != self.current_tag && self.current_tag != TAG_OVERVIEW &&
-                screen_rect.x == w.rect.x && screen_rect.y == w.rect.y
-            {
+            // The overview takes all the windows, hidden or not.
+            if self.current_tag == TAG_OVERVIEW {
+                self.tab_bars.get_mut(&tag).map(|t| t.hide());
+            } else if !shown.contains(&tag) {
                 w.show(false);
+                self.tab_bars.get_mut(&tag).map(|t| t.hide());
+            }
+        }
+        // The current monitor goes last, so windows on several shown workspaces end up where
+        // it puts them.
+        if self.current_tag != TAG_OVERVIEW {
+            let current_tag = self.current_tag;
+            for tag in shown.iter().filter(|t| **t != current_tag) {
+                self.workspaces.get_mut(tag).unwrap().arrange();
             }
         }
 
@@ -889,6 +1946,47 @@ impl WindowManager {
 
         self.current_workspace_mut().arrange();
         self.current_workspace_mut().restack();
+        // The overview hides every tab bar, see above.
+        if self.current_tag != TAG_OVERVIEW {
+            for tag in shown {
+                self.update_tab_bar(tag);
+            }
+        }
+    }
+
+    fn update_tab_bar(&mut self, tag: c_uchar) {
+        let (strip, clients, focused) = {
+            let workspace = self.workspaces.get(&tag).unwrap();
+            (
+                workspace.get_tab_strip(),
+                workspace.select_clients(&|c| !c.is_floating()),
+                workspace.get_current_focused(),
+            )
+        };
+        match strip {
+            Some(rect) => {
+                let display = self.display;
+                let root = self.root;
+                let normal_color = self.colors.normal_border_color;
+                let focused_color = self.colors.focused_border_color;
+                self.tab_bars
+                    .entry(tag)
+                    .or_insert_with(|| {
+                        TabBar::new(display, root, normal_color, focused_color)
+                    })
+                    .update(rect, clients, focused);
+            }
+            None => {
+                self.tab_bars.get_mut(&tag).map(|t| t.hide());
+            }
+        }
+    }
+
+    fn refresh_tab_bar(&mut self) {
+        let focused = self.current_focused();
+        if let Some(t) = self.tab_bars.get_mut(&self.current_tag) {
+            t.set_focused(focused);
+        }
     }
 
     fn update_window_type(&mut self, client: ClientW) {
@@ -922,6 +2020,7 @@ pub trait XWindowManager {
     fn on_client_message(&mut self, event: xlib::XClientMessageEvent);
     fn on_configure_request(&mut self, event: xlib::XConfigureRequestEvent);
     fn on_configure_notify(&mut self, event: xlib::XConfigureEvent);
+    fn on_screen_change(&mut self, event: xlib::XEvent);
     fn on_destroy_notify(&mut self, event: xlib::XDestroyWindowEvent);
     fn on_enter_notify(&mut self, event: xlib::XEnterWindowEvent);
     fn on_expose_notify(&mut self, event: xlib::XExposeEvent);
@@ -936,6 +2035,14 @@ pub trait XWindowManager {
 
 impl XWindowManager for WindowManager {
     fn on_button_press(&mut self, event: xlib::XButtonPressedEvent) {
+        let tab = self.tab_bars
+            .values()
+            .find(|t| t.window() == event.window)
+            .map(|t| t.client_at(event.x));
+        if let Some(client) = tab {
+            client.map(|c| self.set_focus(c));
+            return;
+        }
         if let Some(c) = self.get_client_by_window(event.window) {
             // TODO: ungrab button if current workspace is not the selected one.
             self.set_focus(c.clone());
@@ -972,7 +2079,49 @@ impl XWindowManager for WindowManager {
                 event.data.get_long(2)
             );
         }
+        if event.message_type == atoms::net_current_desktop() {
+            self.select_workspace_at(event.data.get_long(0) as usize);
+            return;
+        }
         if let Some(c) = self.get_client_by_window(event.window) {
+            if event.message_type == atoms::net_active_window() {
+                // Stashed scratchpads are summoned rather than shown where they are.
+                if c.tag() == TAG_SCRATCHPAD {
+                    if let Some(name) = c.get_extra("scratchpad") {
+                        self.toggle_scratchpad(&name);
+                    }
+                } else {
+                    self.show_client(&c);
+                    self.set_focus(c.clone());
+                }
+            }
+            if event.message_type == atoms::net_wm_desktop() {
+                // All desktops is 0xFFFFFFFF, which arrives sign-extended as -1.
+                let desktop = event.data.get_long(0);
+                let sticky = desktop == -1;
+                let tag = if desktop >= 0 {
+                    self.tag_at(desktop as usize)
+                } else {
+                    None
+                };
+                if c.tag() != TAG_SCRATCHPAD && (sticky || tag.is_some()) {
+                    match tag {
+                        Some(tag) => self.move_client_to_tag(c.clone(), tag),
+                        None => self.set_sticky(c.clone(), true),
+                    }
+                    self.arrange_windows();
+                    match self.current_focused() {
+                        Some(c) => self.set_focus(c),
+                        None => unsafe {
+                            xlib::XSetInputFocus(self.display,
+                                                 self.root,
+                                                 xlib::RevertToPointerRoot,
+                                                 xlib::CurrentTime);
+                        },
+                    }
+                    self.do_log();
+                }
+            }
             if event.message_type == atoms::net_wm_state() {
                 if event.data.get_long(1) == atoms::net_wm_state_fullscreen() as c_long ||
                     event.data.get_long(2) == atoms::net_wm_state_fullscreen() as c_long
@@ -1010,7 +2159,7 @@ impl XWindowManager for WindowManager {
                         &mut xa,
                     );
                 }
-            } else if (c.is_sticky() || c.tag() == self.current_tag) && c.is_floating() {
+            } else if self.is_shown(&c) && c.is_floating() {
                 let mut rect = c.get_rect();
                 if event.value_mask & xlib::CWX as c_ulong != 0 {
                     rect.x = event.x;
@@ -1027,7 +2176,7 @@ impl XWindowManager for WindowManager {
                 c.resize(rect, false);
             } else {
                 c.configure();
-                let show = c.tag() == self.current_tag;
+                let show = self.is_shown(&c);
                 c.show(show);
             }
         } else {
@@ -1067,20 +2216,20 @@ impl XWindowManager for WindowManager {
             );
             self.screen_width = event.width;
             self.screen_height = event.height;
+            self.update_screens();
+        }
+    }
 
-            for i in 0..self.config.tags.len() {
-                let tag = self.config.tags[i];
-                let screen_rects = util::get_screen_rects(self.display);
-                let current_rect = screen_rects.get(i);
-                let last_rect = screen_rects.len() - 1;
-                let w = self.workspaces.get_mut(&tag).unwrap();
-                w.update_rect(current_rect.map(|r| r.clone()).unwrap_or(
-                    screen_rects[last_rect].clone(),
-                ));
+    fn on_screen_change(&mut self, event: xlib::XEvent) {
+        let mut event = event;
+        if self.randr_event_base.map(|b| b + xrandr::RRScreenChangeNotify) ==
+            Some(event.get_type())
+        {
+            unsafe {
+                xrandr::XRRUpdateConfiguration(&mut event);
             }
-
-            self.arrange_windows();
         }
+        self.update_screens();
     }
 
     fn on_destroy_notify(&mut self, event: xlib::XDestroyWindowEvent) {
@@ -1097,14 +2246,20 @@ impl XWindowManager for WindowManager {
                 c.window(),
                 c.get_title()
             );
-            if c.tag() != self.current_tag && self.current_tag != TAG_OVERVIEW {
-                self.select_tag(c.tag());
+            if self.current_tag != TAG_OVERVIEW {
+                self.show_client(&c);
             }
         }
     }
 
     fn on_expose_notify(&mut self, event: xlib::XExposeEvent) {
-        debug!("[on_expose_notify]: not implemented!");
+        if event.count == 0 {
+            for t in self.tab_bars.values() {
+                if t.window() == event.window {
+                    t.draw();
+                }
+            }
+        }
     }
 
     fn on_focus_in(&mut self, event: xlib::XFocusInEvent) {
@@ -1181,15 +2336,18 @@ impl XWindowManager for WindowManager {
         if let Some(mut c) = self.get_client_by_window(event.window).as_mut() {
             if event.atom == xlib::XA_WM_NAME || event.atom == atoms::net_wm_name() {
                 c.update_title();
+                self.refresh_tab_bar();
                 self.do_log();
             } else if event.atom == xlib::XA_WM_NORMAL_HINTS {
                 if self.current_tag != TAG_OVERVIEW {
-                    let tag = c.tag();
                     // ignore size hint for overview since the window sizes are
                     // temporary.
                     c.update_size_hints();
                     //                    c.invalidate();
                     // c.show(tag == self.current_tag);
+                    if self.config.respect_size_hints && self.is_shown(&c) {
+                        self.arrange_windows();
+                    }
                 }
             } else if event.atom == atoms::net_wm_window_type() {
                 self.update_window_type(c.clone());
@@ -1243,6 +2401,9 @@ impl XWindowManager for WindowManager {
                     xlib::MotionNotify => self.on_motion_notify(event.into()),
                     xlib::PropertyNotify => self.on_property_notify(event.into()),
                     xlib::UnmapNotify => self.on_unmap_notify(event.into()),
+                    t if self.randr_event_base.map_or(false, |b| {
+                        t == b + xrandr::RRScreenChangeNotify || t == b + xrandr::RRNotify
+                    }) => self.on_screen_change(event),
                     _ => (),
                 }
             }
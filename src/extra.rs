@@ -31,6 +31,29 @@ pub fn add_workspace_user_tag_dmenu(w: &mut WindowManager) {
     }
 }
 
+pub fn select_workspace_dmenu(w: &mut WindowManager) {
+    let names: Vec<String> =
+        w.tags().iter().map(|t| w.workspaces.get(t).unwrap().name.clone()).collect();
+    let args = vec!["-p", "workspace: "];
+    let prompt = prompt::DmenuPrompt::new(&names, &args);
+    match prompt.do_prompt().as_ref() {
+        Ok(result) if !result.trim().is_empty() => w.select_workspace(result.trim()),
+        _ => return,
+    }
+}
+
+pub fn rename_workspace_dmenu(w: &mut WindowManager) {
+    let selections = vec![];
+    let args = vec!["-p", "rename workspace: "];
+    let prompt = prompt::DmenuPrompt::new(&selections, &args);
+    match prompt.do_prompt().as_ref() {
+        Ok(result) if !result.trim().is_empty() => {
+            w.rename_workspace(result.trim());
+        }
+        _ => return,
+    }
+}
+
 pub fn select_window_dmenu(w: &mut WindowManager) {
     let clients = w.all_clients();
     let contents: Vec<String> = clients.iter()
@@ -61,7 +84,7 @@ pub fn select_window_dmenu(w: &mut WindowManager) {
         Ok(result) => {
             if let Some(position) = contents.iter().position(|s| (*s).trim() == result.trim()) {
                 let c = clients[position].clone();
-                w.select_tag(c.tag());
+                w.show_client(&c);
                 w.set_focus(c);
             }
         }
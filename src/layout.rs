@@ -1,15 +1,99 @@
+use std::cmp;
 use std::io::Write;
+use std::os::raw::c_int;
 use std::rc::Rc;
 
 use client::{ClientW, Rect};
 use config::Config;
-use core;
 use workspace::Workspace;
 
+use x11::xlib;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayoutMessage {
+    /// Changes the number of windows in the master area by the given amount.
+    IncMaster(i32),
+    /// Changes the fraction of the screen taken by the master area by the given amount.
+    ResizeMaster(f32),
+    /// Rotates the layout, e.g. moves the master area from the left to the top.
+    Rotate,
+    /// Restores the layout to the state it was created with.
+    Reset,
+    /// Changes the inner and outer gaps by the given number of pixels.
+    IncGaps(c_int),
+    /// Turns gaps on or off.
+    ToggleGaps,
+    /// Restores the gaps to the sizes in the config and turns them on.
+    ResetGaps,
+    /// Turns a Mirror transformer on or off.
+    ToggleMirror,
+    /// Turns a Rotate transformer on or off.
+    ToggleRotate,
+    /// Turns a Magnifier on or off.
+    ToggleMagnifier,
+    /// Chooses how the next window added to a Bsp layout splits the focused one.
+    Presel(Direction),
+    /// Moves the border of the innermost split around the window by the given fraction
+    /// of the split, growing the window for positive amounts.
+    ResizeSplit(xlib::Window, f32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+}
+
+impl Direction {
+    pub fn rotate(self) -> Direction {
+        match self {
+            Direction::Horizontal => Direction::Vertical,
+            Direction::Vertical => Direction::Horizontal,
+        }
+    }
+}
+
 pub trait Layout: LayoutClone {
+    /// Name of the layout, used to select it by name and shown in the status bar.
+    fn name(&self) -> &str;
     fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)>;
     fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect>;
-    fn post(&self, message: &str, window_manager: &mut core::WindowManager) {}
+    /// Like layout_rects, with one weight per client. Layouts that size clients by weight
+    /// give heavier clients more room, each in its own way: MasterStack and CenteredMaster
+    /// share an area in proportion to the weights, Tile splits by each pair of neighbours.
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        self.layout_rects(config, weights.len(), rect)
+    }
+    /// Handles a message sent to the layout. Returns true if the layout changed and the
+    /// workspace needs to be re-arranged.
+    fn handle_message(&mut self, _config: Rc<Config>, _message: &LayoutMessage) -> bool {
+        false
+    }
+
+    /// Area at the top of rect the window manager should draw tabs in, if the layout
+    /// shows tabs for its clients. Like the rects of windows, it does not include the
+    /// window borders on either side.
+    fn tab_strip(&self, _config: Rc<Config>, _rect: Rect) -> Option<Rect> {
+        None
+    }
+
+    /// Whether the layout only shows one window at a time.
+    fn is_monocle(&self) -> bool {
+        false
+    }
+
+    /// Whether the rects depend on which client has the focus, so the workspace has to be
+    /// re-arranged whenever the focus moves.
+    fn depends_on_focus(&self) -> bool {
+        false
+    }
+
+    /// Called when a window is added to the workspace, with the window that had the focus
+    /// at that time. Lets layouts that keep track of windows update their state.
+    fn client_added(&mut self, _window: xlib::Window, _focused: Option<xlib::Window>) {}
+
+    /// Called when a window leaves the workspace.
+    fn client_removed(&mut self, _window: xlib::Window) {}
 }
 
 pub trait LayoutClone {
@@ -30,42 +114,155 @@ impl Clone for Box<Layout> {
     }
 }
 
+const MASTER_RATIO_MIN: f32 = 0.1;
+const MASTER_RATIO_MAX: f32 = 0.9;
+
+// Splits rect into n rects side by side (Horizontal) or stacked (Vertical), leaving a gap
+// between adjacent rects. Use 2 * border_width as gap to leave room for window borders.
+fn split_rect(rect: &Rect, n: usize, direction: Direction, gap: c_int) -> Vec<Rect> {
+    let mut result = Vec::new();
+    if n == 0 {
+        return result;
+    }
+    let horizontal = direction == Direction::Horizontal;
+    let total = if horizontal { rect.width } else { rect.height };
+    let size = cmp::max((total - gap * (n as c_int - 1)) / n as c_int, 0);
+    let mut offset = 0;
+    for i in 0..n {
+        // Once there is no room left, the remaining rects are empty and pile up at the end.
+        let start = cmp::min(offset, total);
+        let length = if i == n - 1 {
+            cmp::max(total - start, 0)
+        } else {
+            cmp::min(size, total - start)
+        };
+        if horizontal {
+            result.push(Rect::new(rect.x + start, rect.y, length, rect.height));
+        } else {
+            result.push(Rect::new(rect.x, rect.y + start, rect.width, length));
+        }
+        offset += size + gap;
+    }
+    result
+}
+
+// Like split_rect, but each rect gets a share of the space proportional to its ratio.
+fn split_rect_ratios(rect: &Rect, ratios: &[f32], direction: Direction, gap: c_int) -> Vec<Rect> {
+    let mut result = Vec::new();
+    if ratios.is_empty() {
+        return result;
+    }
+    let horizontal = direction == Direction::Horizontal;
+    let total = if horizontal { rect.width } else { rect.height };
+    let space = cmp::max(total - gap * (ratios.len() as c_int - 1), 0);
+    let sum: f32 = ratios.iter().sum();
+    let mut offset = 0;
+    for (i, ratio) in ratios.iter().enumerate() {
+        let start = cmp::min(offset, total);
+        let length = if i == ratios.len() - 1 {
+            cmp::max(total - start, 0)
+        } else if sum > 0.0 {
+            cmp::min((space as f32 * ratio / sum) as c_int, total - start)
+        } else {
+            0
+        };
+        if horizontal {
+            result.push(Rect::new(rect.x + start, rect.y, length, rect.height));
+        } else {
+            result.push(Rect::new(rect.x, rect.y + start, rect.width, length));
+        }
+        offset = start + length + gap;
+    }
+    result
+}
+
+// Arranges n rects into a near-square grid. When n is not a perfect fit, the rows at the
+// bottom hold one rect less than the rows at the top.
+fn grid_rects(rect: &Rect, n: usize, gap: c_int) -> Vec<Rect> {
+    let mut result = Vec::new();
+    if n == 0 {
+        return result;
+    }
+    let mut cols = 1;
+    while cols * cols < n {
+        cols += 1;
+    }
+    let rows = (n + cols - 1) / cols;
+    // The first `full_rows` rows hold `cols` rects, the remaining ones `cols - 1`.
+    let full_rows = n - rows * (cols - 1);
+    for (i, row) in split_rect(rect, rows, Direction::Vertical, gap).iter().enumerate() {
+        let count = if i < full_rows { cols } else { cols - 1 };
+        result.extend(split_rect(row, count, Direction::Horizontal, gap));
+    }
+    result
+}
+
+fn client_weights(clients: &[ClientW]) -> Vec<i32> {
+    clients.iter().map(|c| c.weight()).collect()
+}
+
+fn as_ratios(weights: &[i32]) -> Vec<f32> {
+    weights.iter().map(|&w| w as f32).collect()
+}
+
 #[derive(Clone)]
 pub struct Tile;
 
 impl Layout for Tile {
+    fn name(&self) -> &str {
+        "tile"
+    }
+
     fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
         let clients = current_workspace.select_clients(&|c| !c.is_floating());
-        let rects = self.layout_rects(current_workspace.config.clone(), clients.len(), rect);
+        let weights = client_weights(&clients);
+        let rects = self.layout_rects_weighted(current_workspace.config.clone(), &weights, rect);
         clients.into_iter().zip(rects).collect()
     }
 
     fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.layout_rects_weighted(config, &vec![1; n], rect)
+    }
+
+    // Each split divides the remaining area between a client and the next one by their
+    // weights, so equal weights halve it. The area after the split still holds all the
+    // later clients, so sizes follow neighbouring weights rather than being proportional
+    // to them: [1, 1, 1, 3] gives the first client half the screen and the last 3/16.
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        let n = weights.len();
+        if n == 0 {
+            return Vec::new();
+        }
         let mut result = vec![rect];
         let mut direction = 1;
         while result.len() < n {
             let r = result.pop().unwrap();
+            let (w1, w2) = (weights[result.len()], weights[result.len() + 1]);
             if direction == 1 {
-                let r1 = Rect::new(r.x, r.y, r.width / 2 - config.border_width, r.height);
-                let r2 = Rect::new(r.x + r.width / 2 + config.border_width,
+                let split = r.width * w1 / (w1 + w2);
+                let r1 = Rect::new(r.x, r.y, split - config.border_width, r.height);
+                let r2 = Rect::new(r.x + split + config.border_width,
                                    r.y,
-                                   r.width / 2 - config.border_width,
+                                   r.width - split - config.border_width,
                                    r.height);
-                if r1.width < 0 {
-                    result.push(r.clone());
+                if r1.width < 0 || r2.width < 0 {
+                    // No room left: the window gets an empty rect rather than covering
+                    // its sibling.
+                    result.push(Rect::new(r.x, r.y, 0, 0));
                     result.push(r);
                 } else {
                     result.push(r1);
                     result.push(r2);
                 }
             } else {
+                let split = r.height * w1 / (w1 + w2);
+                let r1 = Rect::new(r.x, r.y, r.width, split - config.border_width);
                 let r2 = Rect::new(r.x,
-                                   r.y + r.height / 2 + config.border_width,
+                                   r.y + split + config.border_width,
                                    r.width,
-                                   r.height / 2 - config.border_width);
-                let r1 = Rect::new(r.x, r.y, r.width, r.height / 2 - config.border_width);
-                if r2.height < 0 {
-                    result.push(r.clone());
+                                   r.height - split - config.border_width);
+                if r1.height < 0 || r2.height < 0 {
+                    result.push(Rect::new(r.x, r.y, 0, 0));
                     result.push(r);
                 } else {
                     result.push(r1);
@@ -82,6 +279,10 @@ impl Layout for Tile {
 pub struct FullScreen;
 
 impl Layout for FullScreen {
+    fn name(&self) -> &str {
+        "fullscreen"
+    }
+
     fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
         let clients = current_workspace.select_clients(&|c| !c.is_floating());
         let rects = self.layout_rects(current_workspace.config.clone(), clients.len(), rect);
@@ -91,12 +292,20 @@ impl Layout for FullScreen {
     fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
         vec![rect; n]
     }
+
+    fn is_monocle(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Clone)]
 pub struct Overview;
 
 impl Layout for Overview {
+    fn name(&self) -> &str {
+        "overview"
+    }
+
     fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
         let clients = current_workspace.select_clients(&|c| !c.is_sticky());
         let mut result = Vec::new();
@@ -109,70 +318,1145 @@ impl Layout for Overview {
     }
 
     fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
-        let mut result = vec![rect];
-        let mut direction = 0;
-        while result.len() < n {
-            let mut tmp = Vec::new();
-            for r in &result {
-                if direction == 0 {
-                    tmp.push(Rect::new(r.x,
-                                       r.y,
-                                       r.width / 2 - config.overview_inset,
-                                       r.height - config.overview_inset));
-                    tmp.push(Rect::new(r.x + r.width / 2 + config.overview_inset,
-                                       r.y,
-                                       r.width / 2 - config.overview_inset,
-                                       r.height - config.overview_inset));
-                } else {
-                    tmp.push(Rect::new(r.x,
-                                       r.y,
-                                       r.width - config.overview_inset,
-                                       r.height / 2 - config.overview_inset));
-                    tmp.push(Rect::new(r.x,
-                                       r.y + r.height / 2 + config.overview_inset,
-                                       r.width - config.overview_inset,
-                                       r.height / 2 - config.overview_inset));
-                }
-            }
-            direction = direction ^ 1;
-            result = tmp;
+        grid_rects(&rect, n, config.overview_inset)
+    }
+}
+
+/// One region of a Split layout.
+#[derive(Clone)]
+pub struct Region {
+    /// Share of the space of the Split this region gets, relative to the other regions.
+    pub ratio: f32,
+    /// Number of clients placed in this region, or None for all the remaining ones.
+    pub quota: Option<usize>,
+    /// Layout of the clients inside the region.
+    pub layout: Box<Layout>,
+}
+
+impl Region {
+    pub fn new(ratio: f32, quota: Option<usize>, layout: Box<Layout>) -> Region {
+        Region {
+            ratio: ratio,
+            quota: quota,
+            layout: layout,
+        }
+    }
+}
+
+/// Splits the workspace into regions side by side (Horizontal) or stacked (Vertical).
+/// Clients fill the regions in order, each region taking up to its quota; clients beyond
+/// all quotas go to the last region. Regions without clients are dropped and their space
+/// goes to the others.
+#[derive(Clone)]
+pub struct Split {
+    name: String,
+    direction: Direction,
+    regions: Vec<Region>,
+}
+
+impl Split {
+    pub fn new<T: Into<String>>(name: T, direction: Direction, regions: Vec<Region>) -> Split {
+        assert!(!regions.is_empty(), "a split needs at least one region");
+        Split {
+            name: name.into(),
+            direction: direction,
+            regions: regions,
+        }
+    }
+
+    /// The first client in the left third of the screen, the others laid out by layout in
+    /// the remaining two thirds.
+    pub fn tile13(layout: Box<Layout>) -> Split {
+        Split::new("tile13",
+                   Direction::Horizontal,
+                   vec![Region::new(1.0, Some(1), Box::new(FullScreen)),
+                        Region::new(2.0, None, layout)])
+    }
+
+    // Number of clients in each region.
+    fn counts(&self, n: usize) -> Vec<usize> {
+        let mut left = n;
+        let mut counts = Vec::new();
+        for region in &self.regions {
+            let count = cmp::min(region.quota.unwrap_or(left), left);
+            counts.push(count);
+            left -= count;
         }
+        *counts.last_mut().unwrap() += left;
+        counts
+    }
+}
+
+impl Layout for Split {
+    fn name(&self) -> &str {
+        &self.name
+    }
 
-        result.sort_by_key(|r| (r.y, r.x));
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        let clients = current_workspace.select_clients(&|c| !c.is_floating());
+        let weights = client_weights(&clients);
+        let rects = self.layout_rects_weighted(current_workspace.config.clone(), &weights, rect);
+        clients.into_iter().zip(rects).collect()
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.layout_rects_weighted(config, &vec![1; n], rect)
+    }
+
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        let used: Vec<(&Region, usize)> = self.regions
+            .iter()
+            .zip(self.counts(weights.len()))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        let ratios: Vec<f32> = used.iter().map(|&(r, _)| r.ratio).collect();
+        let areas = split_rect_ratios(&rect, &ratios, self.direction, 2 * config.border_width);
+        let mut result = Vec::new();
+        let mut start = 0;
+        for (&(region, count), area) in used.iter().zip(areas) {
+            let region_weights = &weights[start..start + count];
+            result.extend(region.layout
+                .layout_rects_weighted(config.clone(), region_weights, area));
+            start += count;
+        }
         result
     }
+
+    fn handle_message(&mut self, config: Rc<Config>, message: &LayoutMessage) -> bool {
+        let mut changed = false;
+        for region in self.regions.iter_mut() {
+            changed |= region.layout.handle_message(config.clone(), message);
+        }
+        changed
+    }
+
+    fn depends_on_focus(&self) -> bool {
+        self.regions.iter().any(|r| r.layout.depends_on_focus())
+    }
+
+    fn client_added(&mut self, window: xlib::Window, focused: Option<xlib::Window>) {
+        for region in self.regions.iter_mut() {
+            region.layout.client_added(window, focused);
+        }
+    }
+
+    fn client_removed(&mut self, window: xlib::Window) {
+        for region in self.regions.iter_mut() {
+            region.layout.client_removed(window);
+        }
+    }
 }
 
+/// The first client in the left third of the screen, the others laid out by layout in the
+/// remaining two thirds. Kept for configs written before Split.
+#[deprecated(note = "use Split::tile13")]
 #[derive(Clone)]
 pub struct Tile13 {
     pub layout: Box<Layout>,
 }
 
+#[allow(deprecated)]
+impl Tile13 {
+    fn split(&self) -> Split {
+        Split::tile13(self.layout.clone())
+    }
+}
+
+#[allow(deprecated)]
 impl Layout for Tile13 {
+    fn name(&self) -> &str {
+        "tile13"
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        self.split().layout(current_workspace, rect)
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.split().layout_rects(config, n, rect)
+    }
+
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        self.split().layout_rects_weighted(config, weights, rect)
+    }
+
+    fn handle_message(&mut self, config: Rc<Config>, message: &LayoutMessage) -> bool {
+        self.layout.handle_message(config, message)
+    }
+
+    fn tab_strip(&self, config: Rc<Config>, rect: Rect) -> Option<Rect> {
+        self.split().tab_strip(config, rect)
+    }
+
+    fn depends_on_focus(&self) -> bool {
+        self.layout.depends_on_focus()
+    }
+
+    fn client_added(&mut self, window: xlib::Window, focused: Option<xlib::Window>) {
+        self.layout.client_added(window, focused);
+    }
+
+    fn client_removed(&mut self, window: xlib::Window) {
+        self.layout.client_removed(window);
+    }
+}
+
+#[derive(Clone)]
+pub struct MasterStack {
+    nmaster: usize,
+    ratio: f32,
+    direction: Direction,
+    initial: (usize, f32, Direction),
+}
+
+impl MasterStack {
+    pub fn new(nmaster: usize, ratio: f32) -> MasterStack {
+        MasterStack {
+            nmaster: nmaster,
+            ratio: ratio,
+            direction: Direction::Horizontal,
+            initial: (nmaster, ratio, Direction::Horizontal),
+        }
+    }
+
+    pub fn nmaster(&self) -> usize {
+        self.nmaster
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+}
+
+impl Default for MasterStack {
+    fn default() -> MasterStack {
+        MasterStack::new(1, 0.55)
+    }
+}
+
+impl Layout for MasterStack {
+    fn name(&self) -> &str {
+        "master"
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        let clients = current_workspace.select_clients(&|c| !c.is_floating());
+        let weights = client_weights(&clients);
+        let rects = self.layout_rects_weighted(current_workspace.config.clone(), &weights, rect);
+        clients.into_iter().zip(rects).collect()
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.layout_rects_weighted(config, &vec![1; n], rect)
+    }
+
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        let n = weights.len();
+        let nmaster = cmp::min(self.nmaster, n);
+        let gap = 2 * config.border_width;
+        // The windows inside the master and the stack area are split across the direction
+        // in which the two areas are laid out.
+        let inner = self.direction.rotate();
+        if nmaster == 0 || nmaster == n {
+            return split_rect_ratios(&rect, &as_ratios(weights), inner, gap);
+        }
+        let (master_rect, stack_rect) = match self.direction {
+            Direction::Horizontal => {
+                let master_width = (rect.width as f32 * self.ratio) as c_int;
+                (Rect::new(rect.x, rect.y, master_width - config.border_width, rect.height),
+                 Rect::new(rect.x + master_width + config.border_width,
+                           rect.y,
+                           rect.width - master_width - config.border_width,
+                           rect.height))
+            }
+            Direction::Vertical => {
+                let master_height = (rect.height as f32 * self.ratio) as c_int;
+                (Rect::new(rect.x, rect.y, rect.width, master_height - config.border_width),
+                 Rect::new(rect.x,
+                           rect.y + master_height + config.border_width,
+                           rect.width,
+                           rect.height - master_height - config.border_width))
+            }
+        };
+        let (master_weights, stack_weights) = weights.split_at(nmaster);
+        let mut result = split_rect_ratios(&master_rect, &as_ratios(master_weights), inner, gap);
+        result.extend(split_rect_ratios(&stack_rect, &as_ratios(stack_weights), inner, gap));
+        result
+    }
+
+    fn handle_message(&mut self, _config: Rc<Config>, message: &LayoutMessage) -> bool {
+        match *message {
+            LayoutMessage::IncMaster(delta) => {
+                let nmaster = cmp::max(self.nmaster as i32 + delta, 0) as usize;
+                let changed = nmaster != self.nmaster;
+                self.nmaster = nmaster;
+                changed
+            }
+            LayoutMessage::ResizeMaster(delta) => {
+                self.ratio = (self.ratio + delta).max(MASTER_RATIO_MIN).min(MASTER_RATIO_MAX);
+                true
+            }
+            LayoutMessage::Rotate => {
+                self.direction = self.direction.rotate();
+                true
+            }
+            LayoutMessage::Reset => {
+                let (nmaster, ratio, direction) = self.initial;
+                self.nmaster = nmaster;
+                self.ratio = ratio;
+                self.direction = direction;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Grid;
+
+impl Layout for Grid {
+    fn name(&self) -> &str {
+        "grid"
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        let clients = current_workspace.select_clients(&|c| !c.is_floating());
+        let rects = self.layout_rects(current_workspace.config.clone(), clients.len(), rect);
+        clients.into_iter().zip(rects).collect()
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        grid_rects(&rect, n, 2 * config.border_width)
+    }
+}
+
+#[derive(Clone)]
+pub struct CenteredMaster {
+    ratio: f32,
+    initial_ratio: f32,
+}
+
+impl CenteredMaster {
+    pub fn new(ratio: f32) -> CenteredMaster {
+        CenteredMaster {
+            ratio: ratio,
+            initial_ratio: ratio,
+        }
+    }
+}
+
+impl Default for CenteredMaster {
+    fn default() -> CenteredMaster {
+        CenteredMaster::new(0.5)
+    }
+}
+
+impl Layout for CenteredMaster {
+    fn name(&self) -> &str {
+        "centered"
+    }
+
     fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
         let clients = current_workspace.select_clients(&|c| !c.is_floating());
-        let result = self.layout_rects(current_workspace.config.clone(), clients.len(), rect);
-        clients.into_iter().zip(result).collect()
+        let weights = client_weights(&clients);
+        let rects = self.layout_rects_weighted(current_workspace.config.clone(), &weights, rect);
+        clients.into_iter().zip(rects).collect()
     }
 
     fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.layout_rects_weighted(config, &vec![1; n], rect)
+    }
+
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        let n = weights.len();
+        if n <= 1 {
+            return vec![rect; n];
+        }
+        let gap = 2 * config.border_width;
+        let master_width = (rect.width as f32 * self.ratio) as c_int;
+        // Column boundaries from left to right. With a single stack client there is no
+        // point in keeping an empty column, so the master goes to the left.
+        let edges = if n == 2 {
+            vec![rect.x, rect.x + master_width, rect.x + rect.width]
+        } else {
+            let side_width = (rect.width - master_width) / 2;
+            vec![rect.x,
+                 rect.x + side_width,
+                 rect.x + side_width + master_width,
+                 rect.x + rect.width]
+        };
+        let mut columns = Vec::new();
+        for i in 0..edges.len() - 1 {
+            let left = if i == 0 { edges[i] } else { edges[i] + gap / 2 };
+            let right = if i == edges.len() - 2 {
+                edges[i + 1]
+            } else {
+                edges[i + 1] - gap / 2
+            };
+            columns.push(Rect::new(left, rect.y, cmp::max(right - left, 0), rect.height));
+        }
+
+        if n == 2 {
+            return columns;
+        }
+        // Stack clients alternate between the right and the left column.
+        let right_weights: Vec<f32> = weights[1..].iter().step_by(2).map(|&w| w as f32).collect();
+        let left_weights: Vec<f32> = weights[2..].iter().step_by(2).map(|&w| w as f32).collect();
+        let right = split_rect_ratios(&columns[2], &right_weights, Direction::Vertical, gap);
+        let left = split_rect_ratios(&columns[0], &left_weights, Direction::Vertical, gap);
+        let mut result = vec![columns[1].clone()];
+        for i in 0..n - 1 {
+            if i % 2 == 0 {
+                result.push(right[i / 2].clone());
+            } else {
+                result.push(left[i / 2].clone());
+            }
+        }
+        result
+    }
+
+    fn handle_message(&mut self, _config: Rc<Config>, message: &LayoutMessage) -> bool {
+        match *message {
+            LayoutMessage::ResizeMaster(delta) => {
+                self.ratio = (self.ratio + delta).max(MASTER_RATIO_MIN).min(MASTER_RATIO_MAX);
+                true
+            }
+            LayoutMessage::Reset => {
+                self.ratio = self.initial_ratio;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Tabbed;
+
+impl Layout for Tabbed {
+    fn name(&self) -> &str {
+        "tabbed"
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        let clients = current_workspace.select_clients(&|c| !c.is_floating());
+        let rects = self.layout_rects(current_workspace.config.clone(), clients.len(), rect);
+        clients.into_iter().zip(rects).collect()
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        let tab_height = cmp::min(config.tab_height, rect.height);
+        vec![Rect::new(rect.x, rect.y + tab_height, rect.width, rect.height - tab_height); n]
+    }
+
+    fn tab_strip(&self, config: Rc<Config>, rect: Rect) -> Option<Rect> {
+        Some(Rect::new(rect.x, rect.y, rect.width, cmp::min(config.tab_height, rect.height)))
+    }
+
+    fn is_monocle(&self) -> bool {
+        true
+    }
+}
+
+/// Adds gaps around the rect of the workspace and between the rects produced by another
+/// layout. Gap sizes default to Config::gap_inner and Config::gap_outer.
+#[derive(Clone)]
+pub struct Gaps {
+    pub layout: Box<Layout>,
+    inner: Option<c_int>,
+    outer: Option<c_int>,
+    enabled: bool,
+}
+
+impl Gaps {
+    pub fn new(layout: Box<Layout>) -> Gaps {
+        Gaps {
+            layout: layout,
+            inner: None,
+            outer: None,
+            enabled: true,
+        }
+    }
+
+    fn gaps(&self, config: &Config) -> (c_int, c_int) {
+        (self.inner.unwrap_or(config.gap_inner), self.outer.unwrap_or(config.gap_outer))
+    }
+
+    // The rect left after taking away the outer gap.
+    fn area(&self, config: &Config, rect: Rect) -> Rect {
+        let (_, outer) = self.gaps(config);
+        Rect::new(rect.x + outer,
+                  rect.y + outer,
+                  cmp::max(rect.width - 2 * outer, 0),
+                  cmp::max(rect.height - 2 * outer, 0))
+    }
+
+    // The area grown by half the inner gap, so that shrinking every rect laid out in it by
+    // half the inner gap on each side keeps the outer edges where they are.
+    fn expanded_area(&self, config: &Config, rect: Rect) -> Rect {
+        let (inner, _) = self.gaps(config);
+        let area = self.area(config, rect);
+        Rect::new(area.x - inner / 2,
+                  area.y - inner / 2,
+                  area.width + inner,
+                  area.height + inner)
+    }
+}
+
+fn shrink(rect: Rect, gap: c_int) -> Rect {
+    Rect::new(rect.x + gap / 2,
+              rect.y + gap / 2,
+              cmp::max(rect.width - gap, 0),
+              cmp::max(rect.height - gap, 0))
+}
+
+impl Layout for Gaps {
+    fn name(&self) -> &str {
+        self.layout.name()
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        let config = current_workspace.config.clone();
+        if !self.enabled {
+            return self.layout.layout(current_workspace, rect);
+        }
+        let (inner, _) = self.gaps(&config);
+        let expanded = self.expanded_area(&config, rect);
+        self.layout
+            .layout(current_workspace, expanded)
+            .into_iter()
+            .map(|(c, r)| (c, shrink(r, inner)))
+            .collect()
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.layout_rects_weighted(config, &vec![1; n], rect)
+    }
+
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        if !self.enabled {
+            return self.layout.layout_rects_weighted(config, weights, rect);
+        }
+        let (inner, _) = self.gaps(&config);
+        let expanded = self.expanded_area(&config, rect);
+        self.layout
+            .layout_rects_weighted(config, weights, expanded)
+            .into_iter()
+            .map(|r| shrink(r, inner))
+            .collect()
+    }
+
+    fn handle_message(&mut self, config: Rc<Config>, message: &LayoutMessage) -> bool {
+        match *message {
+            LayoutMessage::IncGaps(delta) => {
+                let (inner, outer) = self.gaps(&config);
+                self.inner = Some(cmp::max(inner + delta, 0));
+                self.outer = Some(cmp::max(outer + delta, 0));
+                self.enabled = true;
+                true
+            }
+            LayoutMessage::ToggleGaps => {
+                self.enabled = !self.enabled;
+                true
+            }
+            LayoutMessage::Reset |
+            LayoutMessage::ResetGaps => {
+                self.inner = None;
+                self.outer = None;
+                self.enabled = true;
+                self.layout.handle_message(config, message);
+                true
+            }
+            _ => self.layout.handle_message(config, message),
+        }
+    }
+
+    fn tab_strip(&self, config: Rc<Config>, rect: Rect) -> Option<Rect> {
+        if !self.enabled {
+            return self.layout.tab_strip(config, rect);
+        }
+        let area = self.area(&config, rect);
+        self.layout.tab_strip(config, area)
+    }
+
+    fn is_monocle(&self) -> bool {
+        self.layout.is_monocle()
+    }
+
+    fn depends_on_focus(&self) -> bool {
+        self.layout.depends_on_focus()
+    }
+
+    fn client_added(&mut self, window: xlib::Window, focused: Option<xlib::Window>) {
+        self.layout.client_added(window, focused);
+    }
+
+    fn client_removed(&mut self, window: xlib::Window) {
+        self.layout.client_removed(window);
+    }
+}
+
+/// Mirrors the rects produced by another layout, horizontally (left becomes right) or
+/// vertically (top becomes bottom).
+#[derive(Clone)]
+pub struct Mirror {
+    pub layout: Box<Layout>,
+    direction: Direction,
+    enabled: bool,
+}
+
+impl Mirror {
+    pub fn new(layout: Box<Layout>, direction: Direction) -> Mirror {
+        Mirror {
+            layout: layout,
+            direction: direction,
+            enabled: true,
+        }
+    }
+
+    fn transform(&self, area: &Rect, rect: Rect) -> Rect {
+        if !self.enabled {
+            return rect;
+        }
+        // Windows extend their rect by the same border on each side as the area does, so
+        // the borders cancel out.
+        match self.direction {
+            Direction::Horizontal => {
+                Rect::new(2 * area.x + area.width - rect.x - rect.width,
+                          rect.y,
+                          rect.width,
+                          rect.height)
+            }
+            Direction::Vertical => {
+                Rect::new(rect.x,
+                          2 * area.y + area.height - rect.y - rect.height,
+                          rect.width,
+                          rect.height)
+            }
+        }
+    }
+}
+
+impl Layout for Mirror {
+    fn name(&self) -> &str {
+        self.layout.name()
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        self.layout
+            .layout(current_workspace, rect.clone())
+            .into_iter()
+            .map(|(c, r)| (c, self.transform(&rect, r)))
+            .collect()
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.layout_rects_weighted(config, &vec![1; n], rect)
+    }
+
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        self.layout
+            .layout_rects_weighted(config, weights, rect.clone())
+            .into_iter()
+            .map(|r| self.transform(&rect, r))
+            .collect()
+    }
+
+    fn handle_message(&mut self, config: Rc<Config>, message: &LayoutMessage) -> bool {
+        match *message {
+            LayoutMessage::ToggleMirror => {
+                self.enabled = !self.enabled;
+                true
+            }
+            LayoutMessage::Reset => {
+                self.enabled = true;
+                self.layout.handle_message(config, message);
+                true
+            }
+            _ => self.layout.handle_message(config, message),
+        }
+    }
+
+    fn tab_strip(&self, config: Rc<Config>, rect: Rect) -> Option<Rect> {
+        self.layout.tab_strip(config, rect.clone()).map(|s| self.transform(&rect, s))
+    }
+
+    fn is_monocle(&self) -> bool {
+        self.layout.is_monocle()
+    }
+
+    fn depends_on_focus(&self) -> bool {
+        self.layout.depends_on_focus()
+    }
+
+    fn client_added(&mut self, window: xlib::Window, focused: Option<xlib::Window>) {
+        self.layout.client_added(window, focused);
+    }
+
+    fn client_removed(&mut self, window: xlib::Window) {
+        self.layout.client_removed(window);
+    }
+}
+
+/// Rotates the rects produced by another layout by 90 degrees clockwise, e.g. the master
+/// area of a left-to-right layout ends up at the top.
+#[derive(Clone)]
+pub struct Rotate {
+    pub layout: Box<Layout>,
+    enabled: bool,
+}
+
+impl Rotate {
+    pub fn new(layout: Box<Layout>) -> Rotate {
+        Rotate {
+            layout: layout,
+            enabled: true,
+        }
+    }
+
+    // The rect the wrapped layout lays out in: rect with width and height swapped.
+    fn rotated_area(&self, rect: &Rect) -> Rect {
+        if self.enabled {
+            Rect::new(rect.x, rect.y, rect.height, rect.width)
+        } else {
+            rect.clone()
+        }
+    }
+
+    fn transform(&self, area: &Rect, rect: Rect) -> Rect {
+        if !self.enabled {
+            return rect;
+        }
+        let u = rect.x - area.x;
+        let v = rect.y - area.y;
+        Rect::new(area.x + area.width - v - rect.height,
+                  area.y + u,
+                  rect.height,
+                  rect.width)
+    }
+}
+
+impl Layout for Rotate {
+    fn name(&self) -> &str {
+        self.layout.name()
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        self.layout
+            .layout(current_workspace, self.rotated_area(&rect))
+            .into_iter()
+            .map(|(c, r)| (c, self.transform(&rect, r)))
+            .collect()
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.layout_rects_weighted(config, &vec![1; n], rect)
+    }
+
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        self.layout
+            .layout_rects_weighted(config, weights, self.rotated_area(&rect))
+            .into_iter()
+            .map(|r| self.transform(&rect, r))
+            .collect()
+    }
+
+    fn handle_message(&mut self, config: Rc<Config>, message: &LayoutMessage) -> bool {
+        match *message {
+            LayoutMessage::ToggleRotate => {
+                self.enabled = !self.enabled;
+                true
+            }
+            LayoutMessage::Reset => {
+                self.enabled = true;
+                self.layout.handle_message(config, message);
+                true
+            }
+            _ => self.layout.handle_message(config, message),
+        }
+    }
+
+    fn tab_strip(&self, config: Rc<Config>, rect: Rect) -> Option<Rect> {
+        // Tabs on the side of the screen are not supported.
+        if self.enabled {
+            None
+        } else {
+            self.layout.tab_strip(config, rect)
+        }
+    }
+
+    fn is_monocle(&self) -> bool {
+        self.layout.is_monocle()
+    }
+
+    fn depends_on_focus(&self) -> bool {
+        self.layout.depends_on_focus()
+    }
+
+    fn client_added(&mut self, window: xlib::Window, focused: Option<xlib::Window>) {
+        self.layout.client_added(window, focused);
+    }
+
+    fn client_removed(&mut self, window: xlib::Window) {
+        self.layout.client_removed(window);
+    }
+}
+
+/// Enlarges the rect of the focused client of another layout by factor, keeping it centered
+/// on its tile as far as the workspace allows.
+#[derive(Clone)]
+pub struct Magnifier {
+    pub layout: Box<Layout>,
+    factor: f32,
+    enabled: bool,
+}
+
+impl Magnifier {
+    pub fn new(layout: Box<Layout>, factor: f32) -> Magnifier {
+        Magnifier {
+            layout: layout,
+            factor: factor,
+            enabled: true,
+        }
+    }
+
+    fn magnify(&self, area: &Rect, rect: Rect) -> Rect {
+        let width = cmp::min((rect.width as f32 * self.factor) as c_int, area.width);
+        let height = cmp::min((rect.height as f32 * self.factor) as c_int, area.height);
+        let x = rect.x + (rect.width - width) / 2;
+        let y = rect.y + (rect.height - height) / 2;
+        Rect::new(cmp::max(area.x, cmp::min(x, area.x + area.width - width)),
+                  cmp::max(area.y, cmp::min(y, area.y + area.height - height)),
+                  width,
+                  height)
+    }
+
+    /// The rects of n clients with the one at index focused magnified, as on a workspace
+    /// where that client has the focus.
+    pub fn focused_rects(&self,
+                         config: Rc<Config>,
+                         n: usize,
+                         focused: usize,
+                         rect: Rect)
+                         -> Vec<Rect> {
+        let rects = self.layout.layout_rects(config, n, rect.clone());
+        self.magnify_focused(&rect, rects, Some(focused))
+    }
+
+    // Magnifies the rect at index focused. A lone client already has all the room there is.
+    fn magnify_focused(&self, area: &Rect, rects: Vec<Rect>, focused: Option<usize>) -> Vec<Rect> {
+        if !self.enabled || rects.len() < 2 {
+            return rects;
+        }
+        rects.into_iter()
+            .enumerate()
+            .map(|(i, r)| if Some(i) == focused { self.magnify(area, r) } else { r })
+            .collect()
+    }
+}
+
+impl Layout for Magnifier {
+    fn name(&self) -> &str {
+        self.layout.name()
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        let (clients, rects): (Vec<ClientW>, Vec<Rect>) =
+            self.layout.layout(current_workspace, rect.clone()).into_iter().unzip();
+        let focused = current_workspace.get_current_focused().and_then(|f| {
+            clients.iter().position(|c| c.window() == f.window())
+        });
+        let rects = self.magnify_focused(&rect, rects, focused);
+        clients.into_iter().zip(rects).collect()
+    }
+
+    // Without clients there is no focus to magnify.
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        self.layout.layout_rects(config, n, rect)
+    }
+
+    fn layout_rects_weighted(&self, config: Rc<Config>, weights: &[i32], rect: Rect) -> Vec<Rect> {
+        self.layout.layout_rects_weighted(config, weights, rect)
+    }
+
+    fn handle_message(&mut self, config: Rc<Config>, message: &LayoutMessage) -> bool {
+        match *message {
+            LayoutMessage::ToggleMagnifier => {
+                self.enabled = !self.enabled;
+                true
+            }
+            LayoutMessage::Reset => {
+                self.enabled = true;
+                self.layout.handle_message(config, message);
+                true
+            }
+            _ => self.layout.handle_message(config, message),
+        }
+    }
+
+    fn tab_strip(&self, config: Rc<Config>, rect: Rect) -> Option<Rect> {
+        self.layout.tab_strip(config, rect)
+    }
+
+    fn is_monocle(&self) -> bool {
+        self.layout.is_monocle()
+    }
+
+    fn depends_on_focus(&self) -> bool {
+        self.enabled || self.layout.depends_on_focus()
+    }
+
+    fn client_added(&mut self, window: xlib::Window, focused: Option<xlib::Window>) {
+        self.layout.client_added(window, focused);
+    }
+
+    fn client_removed(&mut self, window: xlib::Window) {
+        self.layout.client_removed(window);
+    }
+}
+
+#[derive(Clone, Debug)]
+enum BspNode {
+    Leaf(xlib::Window),
+    Split {
+        direction: Direction,
+        ratio: f32,
+        first: Box<BspNode>,
+        second: Box<BspNode>,
+    },
+}
+
+impl BspNode {
+    fn contains(&self, window: xlib::Window) -> bool {
+        match *self {
+            BspNode::Leaf(w) => w == window,
+            BspNode::Split { ref first, ref second, .. } => {
+                first.contains(window) || second.contains(window)
+            }
+        }
+    }
+
+    fn windows(&self, result: &mut Vec<xlib::Window>) {
+        match *self {
+            BspNode::Leaf(w) => result.push(w),
+            BspNode::Split { ref first, ref second, .. } => {
+                first.windows(result);
+                second.windows(result);
+            }
+        }
+    }
+
+    fn last(&self) -> xlib::Window {
+        match *self {
+            BspNode::Leaf(w) => w,
+            BspNode::Split { ref second, .. } => second.last(),
+        }
+    }
+
+    // Splits the leaf of target into target and window. Without a direction, the leaf is
+    // split across the direction of the split it is in.
+    fn insert(self,
+              target: xlib::Window,
+              window: xlib::Window,
+              direction: Option<Direction>,
+              parent: Direction)
+              -> BspNode {
+        match self {
+            BspNode::Leaf(w) if w == target => {
+                BspNode::Split {
+                    direction: direction.unwrap_or(parent.rotate()),
+                    ratio: 0.5,
+                    first: Box::new(BspNode::Leaf(w)),
+                    second: Box::new(BspNode::Leaf(window)),
+                }
+            }
+            BspNode::Split { direction: d, ratio, first, second } => {
+                BspNode::Split {
+                    direction: d,
+                    ratio: ratio,
+                    first: Box::new(first.insert(target, window, direction, d)),
+                    second: Box::new(second.insert(target, window, direction, d)),
+                }
+            }
+            leaf => leaf,
+        }
+    }
+
+    // Removes the leaf of window; its sibling takes the place of their split.
+    fn remove(self, window: xlib::Window) -> Option<BspNode> {
+        match self {
+            BspNode::Leaf(w) => if w == window { None } else { Some(BspNode::Leaf(w)) },
+            BspNode::Split { direction, ratio, first, second } => {
+                match (first.remove(window), second.remove(window)) {
+                    (Some(first), Some(second)) => {
+                        Some(BspNode::Split {
+                            direction: direction,
+                            ratio: ratio,
+                            first: Box::new(first),
+                            second: Box::new(second),
+                        })
+                    }
+                    (Some(node), None) | (None, Some(node)) => Some(node),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+
+    // Grows window by delta in the innermost split around it. Returns false if window is
+    // not in this node or has no split around it.
+    fn resize(&mut self, window: xlib::Window, delta: f32) -> bool {
+        match *self {
+            BspNode::Leaf(_) => false,
+            BspNode::Split { ref mut ratio, ref mut first, ref mut second, .. } => {
+                if first.resize(window, delta) || second.resize(window, delta) {
+                    return true;
+                }
+                let delta = if first.contains(window) {
+                    delta
+                } else if second.contains(window) {
+                    -delta
+                } else {
+                    return false;
+                };
+                *ratio = (*ratio + delta).max(MASTER_RATIO_MIN).min(MASTER_RATIO_MAX);
+                true
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        if let BspNode::Split { ref mut ratio, ref mut first, ref mut second, .. } = *self {
+            *ratio = 0.5;
+            first.reset();
+            second.reset();
+        }
+    }
+
+    fn rects(&self, rect: Rect, gap: c_int, result: &mut Vec<(xlib::Window, Rect)>) {
+        match *self {
+            BspNode::Leaf(w) => result.push((w, rect)),
+            BspNode::Split { direction, ratio, ref first, ref second } => {
+                let areas = split_rect_ratios(&rect, &[ratio, 1.0 - ratio], direction, gap);
+                first.rects(areas[0].clone(), gap, result);
+                second.rects(areas[1].clone(), gap, result);
+            }
+        }
+    }
+}
+
+/// Binary space partitioning: every window is a leaf of a tree of splits. A new window
+/// splits the focused one, in the direction chosen with LayoutMessage::Presel or across
+/// the split the focused window is in.
+#[derive(Clone)]
+pub struct Bsp {
+    root: Option<BspNode>,
+    presel: Option<Direction>,
+}
+
+impl Default for Bsp {
+    fn default() -> Bsp {
+        Bsp {
+            root: None,
+            presel: None,
+        }
+    }
+}
+
+impl Bsp {
+    fn insert(&mut self, window: xlib::Window, focused: Option<xlib::Window>) {
+        if self.root.as_ref().map_or(false, |root| root.contains(window)) {
+            return;
+        }
+        let presel = self.presel.take();
+        self.root = Some(match self.root.take() {
+            None => BspNode::Leaf(window),
+            Some(root) => {
+                let target = match focused {
+                    Some(f) if root.contains(f) => f,
+                    _ => root.last(),
+                };
+                // The root is split as if it were inside a vertical split.
+                root.insert(target, window, presel, Direction::Vertical)
+            }
+        });
+    }
+
+    fn remove(&mut self, window: xlib::Window) {
+        self.root = self.root.take().and_then(|root| root.remove(window));
+    }
+
+    /// Rects of the windows in the tree, in tree order.
+    pub fn window_rects(&self, config: Rc<Config>, rect: Rect) -> Vec<(xlib::Window, Rect)> {
         let mut result = Vec::new();
-        if n == 1 {
-            result.push(rect);
-            return result;
-        }
-        if n > 1 {
-            let first_rect = Rect::new(rect.x,
-                                       rect.y,
-                                       rect.width / 3 - config.border_width,
-                                       rect.height);
-            let other_rect = Rect::new(first_rect.x + first_rect.width + config.border_width,
-                                       rect.y,
-                                       rect.width - first_rect.width - config.border_width,
-                                       rect.height);
-            result.push(first_rect);
-            result.extend(self.layout.layout_rects(config.clone(), n - 1, other_rect));
+        if let Some(ref root) = self.root {
+            root.rects(rect, 2 * config.border_width, &mut result);
         }
         result
     }
-}
\ No newline at end of file
+}
+
+impl Layout for Bsp {
+    fn name(&self) -> &str {
+        "bsp"
+    }
+
+    fn layout(&self, current_workspace: &Workspace, rect: Rect) -> Vec<(ClientW, Rect)> {
+        let clients = current_workspace.select_clients(&|c| !c.is_floating());
+        // Floating windows keep their place in the tree, but do not take up space.
+        let mut tree = self.clone();
+        let mut windows = Vec::new();
+        tree.root.as_ref().map(|root| root.windows(&mut windows));
+        for w in windows {
+            if !clients.iter().any(|c| c.window() == w) {
+                tree.remove(w);
+            }
+        }
+        for c in &clients {
+            if !tree.root.as_ref().map_or(false, |root| root.contains(c.window())) {
+                tree.insert(c.window(), None);
+            }
+        }
+        let rects = tree.window_rects(current_workspace.config.clone(), rect);
+        rects.into_iter()
+            .filter_map(|(w, r)| clients.iter().find(|c| c.window() == w).map(|c| (c.clone(), r)))
+            .collect()
+    }
+
+    fn layout_rects(&self, config: Rc<Config>, n: usize, rect: Rect) -> Vec<Rect> {
+        // Without windows to go by, each window splits the previous one.
+        let mut tree = Bsp::default();
+        for i in 0..n {
+            let focused = if i == 0 { None } else { Some(i as xlib::Window) };
+            tree.insert(i as xlib::Window + 1, focused);
+        }
+        tree.window_rects(config, rect).into_iter().map(|(_, r)| r).collect()
+    }
+
+    fn handle_message(&mut self, _config: Rc<Config>, message: &LayoutMessage) -> bool {
+        match *message {
+            LayoutMessage::Presel(direction) => {
+                self.presel = Some(direction);
+                false
+            }
+            LayoutMessage::ResizeSplit(window, delta) => {
+                self.root.as_mut().map_or(false, |root| root.resize(window, delta))
+            }
+            LayoutMessage::Reset => {
+                self.presel = None;
+                self.root.as_mut().map(|root| root.reset());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn client_added(&mut self, window: xlib::Window, focused: Option<xlib::Window>) {
+        self.insert(window, focused);
+    }
+
+    fn client_removed(&mut self, window: xlib::Window) {
+        self.remove(window);
+    }
+}
@@ -14,6 +14,7 @@ pub mod layout;
 pub mod workspace;
 mod atoms;
 pub mod prompt;
+pub mod tabbar;
 pub mod extra;
 
 #[allow(dead_code, non_upper_case_globals)]
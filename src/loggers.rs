@@ -1,12 +1,12 @@
 use std::collections::HashMap;
 use std::io::Write;
-use std::os::raw::c_uchar;
+use std::os::raw::{c_int, c_uchar};
 use std::process;
 use mustache;
 use mustache::MapBuilder;
 
-use client::ClientW;
-use config::Config;
+use client::{ClientW, Rect};
+use config::{Config, TAG_OVERVIEW};
 use util;
 use workspace::Workspace;
 
@@ -14,6 +14,7 @@ pub struct LoggerConfig {
     client_template: mustache::Template,
     client_selected_template: mustache::Template,
     client_title_length: usize,
+    layout_template: mustache::Template,
     separator: &'static str,
     tag_template: mustache::Template,
     tag_selected_template: mustache::Template,
@@ -35,6 +36,11 @@ impl LoggerConfig {
         self
     }
 
+    pub fn layout_template(mut self, template: &str) -> Self {
+        self.layout_template = mustache::compile_str(template).unwrap();
+        self
+    }
+
     pub fn separator(mut self, s: &'static str) -> Self {
         self.separator = s;
         self
@@ -58,6 +64,7 @@ impl Default for LoggerConfig {
             client_selected_template: mustache::compile_str("[<fc=#FFFF00>{{& content }}</fc>] ")
                 .unwrap(),
             client_title_length: 8,
+            layout_template: mustache::compile_str(" [{{& content }}]").unwrap(),
             separator: " :: ",
             tag_template: mustache::compile_str("{{& content }}").unwrap(),
             tag_selected_template: mustache::compile_str("<fc=#00FF00>{{& content }}</fc> |")
@@ -66,14 +73,28 @@ impl Default for LoggerConfig {
     }
 }
 
+/// What a monitor shows, one per monitor in the order of the screens from the left.
+pub struct MonitorStatus {
+    /// RandR output name, empty if unknown.
+    pub name: String,
+    pub rect: Rect,
+    pub has_bar: bool,
+    /// Whether the monitor has the focus.
+    pub current: bool,
+    /// The tags of the clients on the monitor.
+    pub tags: Vec<c_uchar>,
+    pub layout_name: String,
+    pub clients: Vec<ClientW>,
+    pub focused: Option<ClientW>,
+}
+
 pub trait Logger {
     fn dump(&mut self,
             global_config: &Config,
             workspaces: &HashMap<c_uchar, Workspace>,
+            tags: &[c_uchar],
             clients: &Vec<ClientW>,
-            current_tag: c_uchar,
-            current_clients: &Vec<ClientW>,
-            focused: Option<ClientW>);
+            monitors: &[MonitorStatus]);
 }
 
 pub struct DummyLogger;
@@ -90,35 +111,79 @@ impl Logger for DummyLogger {
     fn dump(&mut self,
             global_config: &Config,
             workspaces: &HashMap<c_uchar, Workspace>,
+            tags: &[c_uchar],
             clients: &Vec<ClientW>,
-            current_tag: c_uchar,
-            current_clients: &Vec<ClientW>,
-            focused: Option<ClientW>) {
+            monitors: &[MonitorStatus]) {
         // Do nothing.
     }
 }
 
+/// Runs one xmobar per monitor with a bar, placed over the bar area of its monitor.
 pub struct XMobarLogger {
     config: LoggerConfig,
+    xmobar_args: Vec<String>,
+    bars: Vec<XMobarBar>,
+}
+
+// An xmobar and the monitor it was started for, known by output name and rect.
+struct XMobarBar {
+    output: String,
+    rect: Rect,
     child: process::Child,
 }
 
+impl XMobarBar {
+    fn is_for(&self, monitor: &MonitorStatus) -> bool {
+        monitor.has_bar && self.output == monitor.name && self.rect == monitor.rect
+    }
+}
+
 impl Drop for XMobarLogger {
     fn drop(&mut self) {
-        self.child.kill().unwrap_or_else(|_| ());
+        for bar in self.bars.iter_mut() {
+            bar.child.kill().unwrap_or_else(|_| ());
+        }
     }
 }
 
 impl XMobarLogger {
     pub fn new(config: LoggerConfig, xmobar_args: &[&str]) -> Self {
-        let child = process::Command::new("xmobar")
-            .stdin(process::Stdio::piped())
-            .args(xmobar_args)
-            .spawn()
-            .expect("cannot spawn xmobar");
         XMobarLogger {
             config: config,
-            child: child,
+            xmobar_args: xmobar_args.iter().map(|a| a.to_string()).collect(),
+            bars: Vec::new(),
+        }
+    }
+
+    // Stops the bars of monitors that are gone, moved or lost their bar, and starts bars on
+    // the monitors that have none.
+    fn update_bars(&mut self, bar_height: c_int, monitors: &[MonitorStatus]) {
+        let (bars, stale): (Vec<XMobarBar>, Vec<XMobarBar>) = self.bars
+            .drain(..)
+            .partition(|b| monitors.iter().any(|m| b.is_for(m)));
+        for mut bar in stale {
+            bar.child.kill().unwrap_or_else(|_| ());
+        }
+        self.bars = bars;
+        for m in monitors {
+            if m.has_bar && !self.bars.iter().any(|b| b.is_for(m)) {
+                let position = format!("Static {{ xpos = {}, ypos = {}, width = {}, height = {} }}",
+                                       m.rect.x,
+                                       m.rect.y,
+                                       m.rect.width,
+                                       bar_height);
+                let child = process::Command::new("xmobar")
+                    .stdin(process::Stdio::piped())
+                    .args(&self.xmobar_args)
+                    .args(&["-p", &position])
+                    .spawn()
+                    .expect("cannot spawn xmobar");
+                self.bars.push(XMobarBar {
+                    output: m.name.clone(),
+                    rect: m.rect.clone(),
+                    child: child,
+                });
+            }
         }
     }
 }
@@ -127,72 +192,90 @@ impl Logger for XMobarLogger {
     fn dump(&mut self,
             global_config: &Config,
             workspaces: &HashMap<c_uchar, Workspace>,
-            clients: &Vec<ClientW>,
-            current_tag: c_uchar,
-            current_clients: &Vec<ClientW>,
-            focused: Option<ClientW>) {
-        fn render<W: Write, T1: Into<String>, T2: Into<String>>(w: &mut W,
-                                                                template: &mustache::Template,
-                                                                to_render: T1,
-                                                                tag: T2) {
-            let content = mustache::MapBuilder::new()
-                .insert_str("content", to_render.into())
-                .insert_str("tag", tag.into())
-                .build();
-            template.render_data(w, &content).unwrap();
-        }
-
-        let mut tags: Vec<char> = clients.iter().map(|c| c.tag() as char).collect();
-        tags.push(current_tag as char);
-        tags.sort();
-        tags.dedup();
-        for t in &tags {
-            let selected_template = if *t == current_tag as char {
-                &self.config.tag_selected_template
-            } else {
-                &self.config.tag_template
-            };
-            if current_tag == 0 {
-                render(self.child.stdin.as_mut().unwrap(),
-                       selected_template,
-                       "Overview",
-                       t.to_string());
-            } else {
-                let string = if let Some(description) =
-                    workspaces.get(&(*t as c_uchar)).unwrap().get_description() {
-                    format!("{} - {}", t, description)
-                } else {
-                    (*t).to_string()
-                };
-                render(self.child.stdin.as_mut().unwrap(),
-                       selected_template,
-                       string,
-                       t.to_string());
+            tags: &[c_uchar],
+            _clients: &Vec<ClientW>,
+            monitors: &[MonitorStatus]) {
+        self.update_bars(global_config.bar_height, monitors);
+        for m in monitors {
+            if let Some(bar) = self.bars.iter_mut().find(|b| b.is_for(m)) {
+                write_status(&self.config,
+                             bar.child.stdin.as_mut().unwrap(),
+                             workspaces,
+                             tags,
+                             m);
             }
         }
-        write!(self.child.stdin.as_mut().unwrap(),
-               "{}",
-               self.config.separator);
-        for i in 0..current_clients.len() {
-            let c = &current_clients[i];
-            let selected_template = match focused.as_ref() {
-                Some(c_focused) if c_focused.window() == c.window() => {
-                    &self.config.client_selected_template
-                }
-                _ => &self.config.client_template,
-            };
-            let msg = if current_tag == 0 {
-                format!("{}@", c.tag() as char)
+    }
+}
+
+// Writes the status line of monitor: the workspaces, the layout and the clients on it.
+fn write_status<W: Write>(config: &LoggerConfig,
+                          out: &mut W,
+                          workspaces: &HashMap<c_uchar, Workspace>,
+                          tags: &[c_uchar],
+                          monitor: &MonitorStatus) {
+    fn render<W: Write, T1: Into<String>, T2: Into<String>>(w: &mut W,
+                                                            template: &mustache::Template,
+                                                            to_render: T1,
+                                                            tag: T2) {
+        let content = mustache::MapBuilder::new()
+            .insert_str("content", to_render.into())
+            .insert_str("tag", tag.into())
+            .build();
+        template.render_data(w, &content).unwrap();
+    }
+
+    let current_tags = &monitor.tags;
+    // Workspaces are listed in order, the empty ones only when on screen.
+    let tags: Vec<c_uchar> = Some(TAG_OVERVIEW)
+        .into_iter()
+        .chain(tags.iter().cloned())
+        .filter(|t| {
+            current_tags.contains(t) ||
+            (*t != TAG_OVERVIEW && workspaces.get(t).unwrap().iter().next().is_some())
+        })
+        .collect();
+    let overview = current_tags.contains(&TAG_OVERVIEW);
+    for t in &tags {
+        let selected_template = if current_tags.contains(t) {
+            &config.tag_selected_template
+        } else {
+            &config.tag_template
+        };
+        let workspace = workspaces.get(t).unwrap();
+        if overview {
+            render(out, selected_template, "Overview", workspace.name.as_str());
+        } else {
+            let string = if let Some(description) = workspace.get_description() {
+                format!("{} - {}", workspace.name, description)
             } else {
-                "".to_string()
+                workspace.name.clone()
             };
-            render(self.child.stdin.as_mut().unwrap(),
-                   selected_template,
-                   format!("{}{} ",
-                           msg,
-                           util::truncate(&c.get_title(), self.config.client_title_length)),
-                   (i + 1).to_string());
+            render(out, selected_template, string, workspace.name.as_str());
         }
-        write!(self.child.stdin.as_mut().unwrap(), "\n");
     }
+    render(out,
+           &config.layout_template,
+           monitor.layout_name.as_str(),
+           workspaces.get(&current_tags[0]).unwrap().name.as_str());
+    write!(out, "{}", config.separator);
+    for i in 0..monitor.clients.len() {
+        let c = &monitor.clients[i];
+        let selected_template = match monitor.focused.as_ref() {
+            Some(c_focused) if c_focused.window() == c.window() => {
+                &config.client_selected_template
+            }
+            _ => &config.client_template,
+        };
+        let msg = if overview {
+            format!("{}@", workspaces.get(&c.tag()).unwrap().name)
+        } else {
+            "".to_string()
+        };
+        render(out,
+               selected_template,
+               format!("{}{} ", msg, util::truncate(&c.get_title(), config.client_title_length)),
+               (i + 1).to_string());
+    }
+    write!(out, "\n");
 }
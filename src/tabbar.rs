@@ -0,0 +1,215 @@
+use std::cmp;
+use std::ffi::CString;
+use std::mem::zeroed;
+use std::os::raw::{c_char, c_int, c_uint, c_ulong};
+use std::ptr::null_mut;
+
+use x11::xlib;
+
+use client::{ClientW, Rect};
+use util;
+
+const TAB_FONT: &'static str = "-*-fixed-medium-r-*-*-13-*-*-*-*-*-*-*,fixed";
+const TAB_PADDING: c_int = 4;
+
+/// A strip of tabs drawn by the window manager itself, one tab per client, used by
+/// tabbed layouts to show which windows are stacked behind the focused one.
+pub struct TabBar {
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    gc: xlib::GC,
+    // A font set rather than a font, so that UTF-8 titles are drawn as such.
+    font: xlib::XFontSet,
+    normal_color: c_ulong,
+    focused_color: c_ulong,
+    text_color: c_ulong,
+    tabs: Vec<(Rect, ClientW)>,
+    focused: Option<xlib::Window>,
+    visible: bool,
+}
+
+impl TabBar {
+    pub fn new(display: *mut xlib::Display,
+               root: xlib::Window,
+               normal_color: c_ulong,
+               focused_color: c_ulong)
+               -> TabBar {
+        unsafe {
+            let screen = xlib::XDefaultScreen(display);
+            let mut wa: xlib::XSetWindowAttributes = zeroed();
+            wa.override_redirect = 1;
+            wa.background_pixel = normal_color;
+            wa.event_mask = xlib::ExposureMask | xlib::ButtonPressMask;
+            let window = xlib::XCreateWindow(display,
+                                             root,
+                                             0,
+                                             0,
+                                             1,
+                                             1,
+                                             0,
+                                             xlib::XDefaultDepth(display, screen),
+                                             xlib::CopyFromParent as c_uint,
+                                             xlib::XDefaultVisual(display, screen),
+                                             xlib::CWOverrideRedirect | xlib::CWBackPixel |
+                                             xlib::CWEventMask,
+                                             &mut wa);
+            let font_name = CString::new(TAB_FONT).unwrap();
+            let mut missing: *mut *mut c_char = null_mut();
+            let mut missing_count: c_int = 0;
+            let mut default_string: *mut c_char = null_mut();
+            let font = xlib::XCreateFontSet(display,
+                                            font_name.as_ptr(),
+                                            &mut missing,
+                                            &mut missing_count,
+                                            &mut default_string);
+            // Characters of the missing charsets are drawn as the default string.
+            if !missing.is_null() {
+                xlib::XFreeStringList(missing);
+            }
+            let gc = xlib::XCreateGC(display, window, 0, null_mut());
+            TabBar {
+                display: display,
+                window: window,
+                gc: gc,
+                font: font,
+                normal_color: normal_color,
+                focused_color: focused_color,
+                text_color: xlib::XWhitePixel(display, screen),
+                tabs: Vec::new(),
+                focused: None,
+                visible: false,
+            }
+        }
+    }
+
+    pub fn window(&self) -> xlib::Window {
+        self.window
+    }
+
+    /// Moves the strip to rect and shows one tab for each client.
+    pub fn update(&mut self, rect: Rect, clients: Vec<ClientW>, focused: Option<ClientW>) {
+        self.tabs.clear();
+        let n = clients.len() as c_int;
+        for (i, c) in clients.into_iter().enumerate() {
+            let i = i as c_int;
+            let x = rect.width * i / cmp::max(n, 1);
+            let width = rect.width * (i + 1) / cmp::max(n, 1) - x;
+            self.tabs.push((Rect::new(x, 0, width, rect.height), c));
+        }
+        self.focused = focused.map(|c| c.window());
+        if self.tabs.is_empty() || rect.width <= 0 || rect.height <= 0 {
+            self.hide();
+            return;
+        }
+        unsafe {
+            xlib::XMoveResizeWindow(self.display,
+                                    self.window,
+                                    rect.x,
+                                    rect.y,
+                                    rect.width as c_uint,
+                                    rect.height as c_uint);
+            if !self.visible {
+                xlib::XMapRaised(self.display, self.window);
+            }
+        }
+        self.visible = true;
+        self.draw();
+    }
+
+    pub fn hide(&mut self) {
+        if self.visible {
+            unsafe {
+                xlib::XUnmapWindow(self.display, self.window);
+            }
+            self.visible = false;
+        }
+    }
+
+    pub fn set_focused(&mut self, focused: Option<ClientW>) {
+        self.focused = focused.map(|c| c.window());
+        self.draw();
+    }
+
+    pub fn draw(&self) {
+        if !self.visible {
+            return;
+        }
+        for &(ref r, ref c) in &self.tabs {
+            let background = if Some(c.window()) == self.focused {
+                self.focused_color
+            } else {
+                self.normal_color
+            };
+            unsafe {
+                xlib::XSetForeground(self.display, self.gc, background);
+                xlib::XFillRectangle(self.display,
+                                     self.window,
+                                     self.gc,
+                                     r.x,
+                                     r.y,
+                                     r.width as c_uint,
+                                     r.height as c_uint);
+                // Separator between tabs.
+                xlib::XSetForeground(self.display, self.gc, self.focused_color);
+                xlib::XDrawLine(self.display,
+                                self.window,
+                                self.gc,
+                                r.x,
+                                0,
+                                r.x,
+                                r.height);
+            }
+            // Narrow tabs have no room for text.
+            if self.font.is_null() || r.width <= 2 * TAB_PADDING {
+                continue;
+            }
+            let title = c.get_title();
+            let mut text: &str = &title;
+            unsafe {
+                let max_width = r.width - 2 * TAB_PADDING;
+                while !text.is_empty() &&
+                      xlib::Xutf8TextEscapement(self.font,
+                                                text.as_ptr() as *const c_char,
+                                                text.len() as c_int) > max_width {
+                    let len = text.chars().count();
+                    text = util::truncate(text, len - 1);
+                }
+                let extent = &(*xlib::XExtentsOfFontSet(self.font)).max_logical_extent;
+                let ascent = -extent.y as c_int;
+                let descent = extent.height as c_int - ascent;
+                xlib::XSetForeground(self.display, self.gc, self.text_color);
+                xlib::Xutf8DrawString(self.display,
+                                      self.window,
+                                      self.font,
+                                      self.gc,
+                                      r.x + TAB_PADDING,
+                                      (r.height + ascent - descent) / 2,
+                                      text.as_ptr() as *const c_char,
+                                      text.len() as c_int);
+            }
+        }
+        unsafe {
+            xlib::XFlush(self.display);
+        }
+    }
+
+    /// Returns the client whose tab is under x, relative to the strip.
+    pub fn client_at(&self, x: c_int) -> Option<ClientW> {
+        self.tabs
+            .iter()
+            .find(|&&(ref r, _)| x >= r.x && x < r.x + r.width)
+            .map(|&(_, ref c)| c.clone())
+    }
+}
+
+impl Drop for TabBar {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.font.is_null() {
+                xlib::XFreeFontSet(self.display, self.font);
+            }
+            xlib::XFreeGC(self.display, self.gc);
+            xlib::XDestroyWindow(self.display, self.window);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_int, c_uint, c_void};
 use std::mem::zeroed;
 use std::process;
@@ -6,6 +6,7 @@ use std::slice;
 
 use x11::xlib;
 use x11::xinerama;
+use x11::xrandr;
 use client::Rect;
 
 use xproto;
@@ -28,15 +29,63 @@ macro_rules! x_disable_error_unsafe {
 
 #[macro_export]
 macro_rules! define_tags (
+    // Keys address workspaces by name, so a workspace that was deleted comes back when its
+    // key is pressed.
     ( $modkey: expr, $mod_mask: expr, [$($x: expr), *]) => {
         {
             let tag_keys: Vec<(c_uint, c_uint, WmAction)> = vec![
-                $(($modkey, $x as c_uint, Box::new(|w| w.select_tag($x as c_uchar))), )*
-                $(($modkey | $mod_mask, $x as c_uint, Box::new(|w| w.add_tag($x as c_uchar))),)*
+                $(($modkey, $x as c_uint, Box::new(|w| w.select_workspace(&$x.to_string()))), )*
+                $(($modkey | $mod_mask,
+                   $x as c_uint,
+                   Box::new(|w| if let Some(t) = w.workspace_named(&$x.to_string()) {
+                       w.add_tag(t)
+                   })),)*
             ];
             (tag_keys, vec![$($x as c_uchar, )*])
         }
     };
+    // Also binds $modkey | $send_mask to sending the focused window to a tag without
+    // following it there.
+    ( $modkey: expr, $mod_mask: expr, $send_mask: expr, [$($x: expr), *]) => {
+        {
+            let (mut tag_keys, tags) = define_tags!($modkey, $mod_mask, [$($x), *]);
+            let send_keys: Vec<(c_uint, c_uint, WmAction)> = vec![
+                $(($modkey | $send_mask,
+                   $x as c_uint,
+                   Box::new(|w| if let Some(t) = w.workspace_named(&$x.to_string()) {
+                       w.send_to_tag(t)
+                   })),)*
+            ];
+            tag_keys.extend(send_keys);
+            (tag_keys, tags)
+        }
+    };
+    // Also binds $modkey | $view_mask to showing a tag along with the ones on screen, and
+    // $modkey | $toggle_mask to putting the focused window on a tag as well.
+    ( $modkey: expr,
+      $mod_mask: expr,
+      $send_mask: expr,
+      $view_mask: expr,
+      $toggle_mask: expr,
+      [$($x: expr), *]) => {
+        {
+            let (mut tag_keys, tags) = define_tags!($modkey, $mod_mask, $send_mask, [$($x), *]);
+            let toggle_keys: Vec<(c_uint, c_uint, WmAction)> = vec![
+                $(($modkey | $view_mask,
+                   $x as c_uint,
+                   Box::new(|w| if let Some(t) = w.workspace_named(&$x.to_string()) {
+                       w.toggle_view(t)
+                   })),)*
+                $(($modkey | $toggle_mask,
+                   $x as c_uint,
+                   Box::new(|w| if let Some(t) = w.workspace_named(&$x.to_string()) {
+                       w.toggle_tag(t)
+                   })),)*
+            ];
+            tag_keys.extend(toggle_keys);
+            (tag_keys, tags)
+        }
+    };
 );
 
 #[allow(unused_variables)]
@@ -126,33 +175,89 @@ pub fn truncate(s: &str, max_chars: usize) -> &str {
     }
 }
 
-pub fn get_screen_rects(display: *mut xlib::Display) -> Vec<Rect> {
+/// A monitor as the X server reports it.
+#[derive(Clone, Debug)]
+pub struct Screen {
+    /// Name of the RandR output, e.g. "eDP-1", or empty if RandR is not available.
+    pub name: String,
+    /// Whether RandR marks the monitor as the primary one.
+    pub primary: bool,
+    pub rect: Rect,
+}
+
+/// The monitors from left to right, found with RandR, then Xinerama, then the size of the
+/// display.
+pub fn get_screens(display: *mut xlib::Display) -> Vec<Screen> {
     let screen = unsafe { xlib::XDefaultScreen(display) };
     let mut result = Vec::new();
     let mut number: c_int = 0;
-    let info: *mut xinerama::XineramaScreenInfo =
-        unsafe { xinerama::XineramaQueryScreens(display, &mut number) };
-    if !info.is_null() {
-        let info_array: &[xinerama::XineramaScreenInfo] =
-            unsafe { slice::from_raw_parts(info, number as usize) };
-        for i in info_array {
-            result.push(Rect::new(i.x_org as c_int,
-                                  i.y_org as c_int,
-                                  i.width as c_int,
-                                  i.height as c_int));
+    // RandR monitors also cover the ones set up with xrandr --setmonitor.
+    let mut event_base: c_int = 0;
+    let mut error_base: c_int = 0;
+    if unsafe { xrandr::XRRQueryExtension(display, &mut event_base, &mut error_base) } != 0 {
+        let root = unsafe { xlib::XRootWindow(display, screen) };
+        let monitors = unsafe { xrandr::XRRGetMonitors(display, root, 1, &mut number) };
+        if !monitors.is_null() {
+            let monitor_array = unsafe { slice::from_raw_parts(monitors, number as usize) };
+            for m in monitor_array {
+                let name = unsafe {
+                    let atom_name = xlib::XGetAtomName(display, m.name);
+                    if atom_name.is_null() {
+                        String::new()
+                    } else {
+                        let name = CStr::from_ptr(atom_name).to_string_lossy().into_owned();
+                        xlib::XFree(atom_name as *mut c_void);
+                        name
+                    }
+                };
+                result.push(Screen {
+                    name: name,
+                    primary: m.primary != 0,
+                    rect: Rect::new(m.x, m.y, m.width, m.height),
+                });
+            }
+            unsafe {
+                xrandr::XRRFreeMonitors(monitors);
+            }
         }
-        unsafe {
-            xlib::XFree(info as *mut c_void);
+    }
+    if result.is_empty() {
+        let info: *mut xinerama::XineramaScreenInfo =
+            unsafe { xinerama::XineramaQueryScreens(display, &mut number) };
+        if !info.is_null() {
+            let info_array: &[xinerama::XineramaScreenInfo] =
+                unsafe { slice::from_raw_parts(info, number as usize) };
+            for i in info_array {
+                result.push(Screen {
+                    name: String::new(),
+                    primary: false,
+                    rect: Rect::new(i.x_org as c_int,
+                                    i.y_org as c_int,
+                                    i.width as c_int,
+                                    i.height as c_int),
+                });
+            }
+            unsafe {
+                xlib::XFree(info as *mut c_void);
+            }
         }
-    } else {
+    }
+    // Xinerama may be missing or list no screens at all.
+    if result.is_empty() {
         let rect = unsafe {
             Rect::new(0,
                       0,
                       xlib::XDisplayWidth(display, screen),
                       xlib::XDisplayHeight(display, screen))
         };
-        result.push(rect);
+        result.push(Screen {
+            name: String::new(),
+            primary: false,
+            rect: rect,
+        });
     }
+    // The server may list monitors in any order.
+    result.sort_by_key(|s| (s.rect.x, s.rect.y));
     result
 }
 
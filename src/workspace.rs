@@ -1,3 +1,4 @@
+use std::cmp;
 use std::collections::VecDeque;
 use std::io::Write;
 use std::mem::zeroed;
@@ -7,7 +8,7 @@ use std::rc::Rc;
 use atoms;
 use client::{ClientW, Rect};
 use config::{Config, TAG_OVERVIEW};
-use layout::Layout;
+use layout::{Layout, LayoutMessage};
 use util;
 
 use x11::xlib;
@@ -24,7 +25,12 @@ pub struct Workspace {
     clients_prev: VecDeque<ClientW>,
     clients_next: VecDeque<ClientW>,
     description: Option<String>,
-    layout: Box<Layout + 'static>,
+    layouts: Vec<Box<Layout + 'static>>,
+    layout_index: usize,
+    /// Whether the bar is on the monitor showing the workspace.
+    pub has_bar: bool,
+    /// What the workspace is called, unique among workspaces. Defaults to its tag.
+    pub name: String,
     pub rect: Rect,
     pub tag: c_uchar,
 }
@@ -35,9 +41,10 @@ impl Workspace {
         anchor_window: xlib::Window,
         tag: c_uchar,
         description: Option<String>,
-        layout: Box<Layout + 'static>,
+        layouts: Vec<Box<Layout + 'static>>,
         rect: Rect,
     ) -> Self {
+        assert!(!layouts.is_empty(), "a workspace needs at least one layout");
         Workspace {
             anchor_window: anchor_window,
             client_current: None,
@@ -45,7 +52,10 @@ impl Workspace {
             clients_next: VecDeque::new(),
             description: description,
             config: config,
-            layout: layout,
+            layouts: layouts,
+            layout_index: 0,
+            has_bar: false,
+            name: (tag as char).to_string(),
             rect: rect,
             tag: tag,
         }
@@ -72,20 +82,48 @@ impl Workspace {
                 }
             }
         }
+        if self.current_layout().depends_on_focus() {
+            self.arrange();
+        }
         self.restack();
     }
 
+    /// Moves the workspace to rect, e.g. another monitor. Floating windows keep their place
+    /// relative to the workspace, and fullscreen ones cover the new rect.
     pub fn update_rect(&mut self, rect: Rect) {
+        let old = self.rect.clone();
+        let tag = self.tag;
+        for c in self.iter_mut() {
+            // Windows on several workspaces go with the one they belong to.
+            if c.tag() != tag {
+                continue;
+            }
+            if c.is_fullscreen() {
+                c.set_size(rect.x, rect.y, rect.width, rect.height);
+            } else if c.is_floating() {
+                let r = c.get_rect();
+                let x = cmp::min(rect.x + r.x - old.x, rect.x + rect.width - r.width);
+                let y = cmp::min(rect.y + r.y - old.y, rect.y + rect.height - r.height);
+                c.set_size(cmp::max(x, rect.x), cmp::max(y, rect.y), r.width, r.height);
+            }
+        }
         self.rect = rect;
     }
 
     pub fn clear(&mut self) {
+        let windows: Vec<xlib::Window> = self.iter().map(|c| c.window()).collect();
+        for w in windows {
+            self.notify_removed(w);
+        }
         self.clients_prev.clear();
         self.clients_next.clear();
         self.client_current = None;
     }
 
     pub fn detach_current(&mut self) -> Option<ClientW> {
+        if let Some(window) = self.client_current.as_ref().map(|c| c.window()) {
+            self.notify_removed(window);
+        }
         if self.client_current.is_some() {
             if self.clients_next.len() > 0 {
                 self.shift_focus(FocusShift::Forward);
@@ -125,7 +163,32 @@ impl Workspace {
     }
 
     pub fn get_layout(&self, rect: Rect) -> Vec<(ClientW, Rect)> {
-        self.layout.layout(self, rect)
+        self.current_layout().layout(self, rect)
+    }
+
+    pub fn current_layout(&self) -> &Layout {
+        &*self.layouts[self.layout_index]
+    }
+
+    pub fn get_layout_name(&self) -> &str {
+        self.current_layout().name()
+    }
+
+    pub fn next_layout(&mut self) {
+        self.layout_index = (self.layout_index + 1) % self.layouts.len();
+    }
+
+    pub fn prev_layout(&mut self) {
+        self.layout_index = (self.layout_index + self.layouts.len() - 1) % self.layouts.len();
+    }
+
+    pub fn select_layout(&mut self, name: &str) -> bool {
+        if let Some(index) = self.layouts.iter().position(|l| l.name() == name) {
+            self.layout_index = index;
+            true
+        } else {
+            false
+        }
     }
 
     pub fn kill_client(&mut self) {
@@ -141,6 +204,10 @@ impl Workspace {
     }
 
     pub fn new_client(&mut self, client: ClientW, at_focus: bool) {
+        let focused = self.client_current.as_ref().map(|c| c.window());
+        for layout in self.layouts.iter_mut() {
+            layout.client_added(client.window(), focused);
+        }
         if !at_focus {
             self.clients_prev.push_front(client);
             while self.clients_prev.len() > 0 {
@@ -152,6 +219,14 @@ impl Workspace {
         }
     }
 
+    // Tells every layout, not only the current one, so that switching layouts keeps their
+    // state in line with the clients.
+    fn notify_removed(&mut self, window: xlib::Window) {
+        for layout in self.layouts.iter_mut() {
+            layout.client_removed(window);
+        }
+    }
+
     fn push_next(&mut self, client: Option<ClientW>) {
         if let Some(c) = client {
             self.clients_next.push_front(c);
@@ -165,6 +240,7 @@ impl Workspace {
     }
 
     pub fn remove_client(&mut self, client: ClientW) {
+        self.notify_removed(client.window());
         if let Some(position) = self.clients_prev.iter().position(
             |c| c.window() == client.window(),
         )
@@ -217,6 +293,27 @@ impl Workspace {
         }
     }
 
+    pub fn send_layout_message(&mut self, message: &LayoutMessage) -> bool {
+        // Gaps are the same in all the layouts of a workspace, so that cycling layouts keeps
+        // them as they were set.
+        let others = match *message {
+            LayoutMessage::IncGaps(_) |
+            LayoutMessage::ToggleGaps => Some(message.clone()),
+            LayoutMessage::Reset => Some(LayoutMessage::ResetGaps),
+            _ => None,
+        };
+        let index = self.layout_index;
+        let mut changed = self.layouts[index].handle_message(self.config.clone(), message);
+        if let Some(m) = others {
+            for (i, l) in self.layouts.iter_mut().enumerate() {
+                if i != index {
+                    changed = l.handle_message(self.config.clone(), &m) || changed;
+                }
+            }
+        }
+        changed
+    }
+
     pub fn set_description<T: Into<String>>(&mut self, description: T) {
         self.description = Some(description.into());
     }
@@ -292,42 +389,78 @@ impl Workspace {
         self.detach_current().map(|c| self.new_client(c, false));
     }
 
-    pub fn arrange(&mut self) {
-        let bar_height = if self.rect.x == 0 {
+    // Border width of tiled windows. With smart borders, a tiled window that is the only
+    // one visible goes without borders.
+    fn tiled_border_width(&self) -> c_int {
+        if !self.config.smart_borders || self.tag == TAG_OVERVIEW {
+            return self.config.border_width;
+        }
+        let tiled = self.iter().filter(|c| !c.is_floating()).count();
+        if tiled == 1 || self.current_layout().is_monocle() {
+            0
+        } else {
+            self.config.border_width
+        }
+    }
+
+    // The area available to tiled windows, leaving room for the bar and window borders.
+    fn layout_rect(&self) -> Rect {
+        let border_width = self.tiled_border_width();
+        let bar_height = if self.has_bar {
             self.config.bar_height
         } else {
-            self.rect.y
+            0
         };
+        Rect::new(
+            self.rect.x,
+            self.rect.y + bar_height,
+            self.rect.width - 2 * border_width,
+            self.rect.height - bar_height - 2 * border_width,
+        )
+    }
+
+    pub fn get_tab_strip(&self) -> Option<Rect> {
+        // Tabs span the borders of the windows below them.
+        let border_width = self.tiled_border_width();
+        self.current_layout()
+            .tab_strip(self.config.clone(), self.layout_rect())
+            .map(|s| Rect::new(s.x, s.y, s.width + 2 * border_width, s.height))
+    }
+
+    pub fn arrange(&mut self) {
+        let layout_rect = self.layout_rect();
+        let border_width = self.tiled_border_width();
 
         // TODO: 1) Handle sticky windows as well
         //       2) Handle other multiple screen layout
-        let strategy = self.get_layout(Rect::new(
-            self.rect.x,
-            bar_height,
-            self.rect.width - 2 * self.config.border_width,
-            self.rect.height - bar_height - 2 * self.config.border_width,
-        ));
+        let strategy = self.get_layout(layout_rect.clone());
         for (mut c, r) in strategy {
             if self.tag == TAG_OVERVIEW {
                 c.resize(r, true);
                 continue;
             }
             let target_rect = if c.is_maximized() {
-                Rect::new(
-                    self.rect.x,
-                    bar_height,
-                    self.rect.width - 2 * self.config.border_width,
-                    self.rect.height - bar_height - 2 * self.config.border_width,
-                )
+                layout_rect.clone()
             } else {
                 r
             };
+            let target_rect = if self.config.respect_size_hints {
+                c.fit_size_hints(target_rect)
+            } else {
+                target_rect
+            };
+            if !c.is_fullscreen() {
+                c.set_border_width(border_width);
+            }
             c.resize(target_rect, false);
         }
 
         if self.tag != TAG_OVERVIEW {
             let mut floating_clients = self.select_clients(&|c| c.is_floating() == true);
             for fc in floating_clients.iter_mut() {
+                if !fc.is_fullscreen() {
+                    fc.set_border_width(self.config.border_width);
+                }
                 let rect = fc.get_rect();
                 fc.resize(rect, false);
                 fc.raise_window();
@@ -0,0 +1,286 @@
+extern crate rswm;
+
+use std::os::raw::c_int;
+use std::rc::Rc;
+
+use rswm::client::Rect;
+use rswm::config::Config;
+use rswm::layout::{Bsp, CenteredMaster, Direction, FullScreen, Gaps, Grid, Layout,
+                   LayoutMessage, Magnifier, MasterStack, Mirror, Overview, Region, Rotate, Split,
+                   Tabbed, Tile};
+
+const MAX_CLIENTS: usize = 50;
+
+// Screens of different shapes, given as the area a workspace hands to its layout: the
+// screen minus the bar and the borders of the windows.
+fn screens(config: &Config) -> Vec<Rect> {
+    let bw = config.border_width;
+    [(0, 0, 1920, 1080), (0, 0, 1080, 1920), (1920, 0, 1280, 1024), (0, 0, 640, 480)]
+        .iter()
+        .map(|&(x, y, width, height)| {
+            Rect::new(x,
+                      y + config.bar_height,
+                      width - 2 * bw,
+                      height - config.bar_height - 2 * bw)
+        })
+        .collect()
+}
+
+// Every built-in layout, with modifiers wrapped around the layouts they make sense with.
+// Each entry says whether the layout is expected to keep windows apart.
+fn layouts() -> Vec<(Box<Layout>, bool)> {
+    let mut rotated = MasterStack::default();
+    rotated.handle_message(Rc::new(Config::default()), &LayoutMessage::Rotate);
+    vec![(Box::new(Tile), true),
+         (Box::new(FullScreen), false),
+         (Box::new(Overview), true),
+         (Box::new(Split::tile13(Box::new(Tile))), true),
+         (Box::new(Split::tile13(Box::new(FullScreen))), false),
+         (Box::new(Split::new("split",
+                              Direction::Horizontal,
+                              vec![Region::new(0.3, Some(2), Box::new(MasterStack::new(2, 0.5))),
+                                   Region::new(0.7, None, Box::new(Grid))])),
+          true),
+         (Box::new(Split::new("split",
+                              Direction::Vertical,
+                              vec![Region::new(1.0, Some(1), Box::new(FullScreen)),
+                                   Region::new(1.0, Some(3), Box::new(Tile)),
+                                   Region::new(2.0, Some(0), Box::new(Grid)),
+                                   Region::new(1.0, Some(4), Box::new(Grid))])),
+          true),
+         (Box::new(MasterStack::default()), true),
+         (Box::new(MasterStack::new(3, 0.3)), true),
+         (Box::new(rotated), true),
+         (Box::new(Grid), true),
+         (Box::new(CenteredMaster::default()), true),
+         (Box::new(Tabbed), false),
+         (Box::new(Gaps::new(Box::new(MasterStack::default()))), true),
+         (Box::new(Gaps::new(Box::new(Grid))), true),
+         (Box::new(Mirror::new(Box::new(MasterStack::default()), Direction::Horizontal)), true),
+         (Box::new(Mirror::new(Box::new(CenteredMaster::default()), Direction::Vertical)), true),
+         (Box::new(Rotate::new(Box::new(MasterStack::default()))), true),
+         (Box::new(Rotate::new(Box::new(Tile))), true),
+         (Box::new(Bsp::default()), true),
+         (Box::new(Magnifier::new(Box::new(MasterStack::default()), 1.5)), true)]
+}
+
+// The rect a window covers on screen, including its borders.
+fn outer(rect: &Rect, border_width: c_int) -> Rect {
+    Rect::new(rect.x,
+              rect.y,
+              rect.width + 2 * border_width,
+              rect.height + 2 * border_width)
+}
+
+fn is_empty(rect: &Rect) -> bool {
+    rect.width == 0 || rect.height == 0
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height &&
+    b.y < a.y + a.height
+}
+
+fn inside(rect: &Rect, area: &Rect) -> bool {
+    rect.x >= area.x && rect.y >= area.y && rect.x + rect.width <= area.x + area.width &&
+    rect.y + rect.height <= area.y + area.height
+}
+
+// Weights between 1 and 5 that vary from client to client.
+fn uneven_weights(n: usize) -> Vec<i32> {
+    (0..n).map(|i| (i * 7 % 5) as i32 + 1).collect()
+}
+
+// Runs check on the rects of every layout for every screen and every client count, once
+// with equal weights and once with uneven ones.
+fn for_all_layouts<F>(check: F)
+    where F: Fn(&Layout, bool, &Rect, usize, &[Rect])
+{
+    let config = Rc::new(Config::default());
+    for (layout, apart) in layouts() {
+        for screen in screens(&config) {
+            for n in 0..MAX_CLIENTS + 1 {
+                let rects = layout.layout_rects(config.clone(), n, screen.clone());
+                check(&*layout, apart, &screen, n, &rects);
+                let weights = uneven_weights(n);
+                let rects = layout.layout_rects_weighted(config.clone(), &weights, screen.clone());
+                check(&*layout, apart, &screen, n, &rects);
+            }
+        }
+    }
+}
+
+#[test]
+fn one_rect_per_client() {
+    for_all_layouts(|layout, _, screen, n, rects| {
+        assert_eq!(rects.len(),
+                   n,
+                   "{} on {:?} gave {} rects for {} clients",
+                   layout.name(),
+                   screen,
+                   rects.len(),
+                   n);
+    });
+}
+
+#[test]
+fn sizes_are_not_negative() {
+    for_all_layouts(|layout, _, screen, n, rects| {
+        for r in rects {
+            assert!(r.width >= 0 && r.height >= 0,
+                    "{} on {:?} with {} clients gave {:?}",
+                    layout.name(),
+                    screen,
+                    n,
+                    r);
+        }
+    });
+}
+
+#[test]
+fn rects_stay_on_screen() {
+    for_all_layouts(|layout, _, screen, n, rects| {
+        for r in rects {
+            assert!(inside(r, screen),
+                    "{} on {:?} with {} clients gave {:?}",
+                    layout.name(),
+                    screen,
+                    n,
+                    r);
+        }
+    });
+}
+
+#[test]
+fn windows_do_not_overlap() {
+    let border_width = Config::default().border_width;
+    for_all_layouts(|layout, apart, screen, n, rects| {
+        if !apart {
+            return;
+        }
+        // Windows that did not fit get empty rects; there is nothing of them to overlap.
+        let visible: Vec<Rect> = rects.iter()
+            .filter(|r| !is_empty(r))
+            .map(|r| outer(r, border_width))
+            .collect();
+        for (i, a) in visible.iter().enumerate() {
+            for b in visible.iter().skip(i + 1) {
+                assert!(!overlaps(a, b),
+                        "{} on {:?} with {} clients: {:?} overlaps {:?}",
+                        layout.name(),
+                        screen,
+                        n,
+                        a,
+                        b);
+            }
+        }
+    });
+}
+
+#[test]
+fn monocle_layouts_cover_the_same_area() {
+    for_all_layouts(|layout, _, screen, n, rects| {
+        if !layout.is_monocle() {
+            return;
+        }
+        for r in rects {
+            assert_eq!(r, &rects[0], "{} on {:?} with {} clients", layout.name(), screen, n);
+        }
+    });
+}
+
+#[test]
+fn heavier_clients_get_more_room() {
+    let config = Rc::new(Config::default());
+    let screen = screens(&config)[0].clone();
+    let area = |r: &Rect| r.width * r.height;
+    let layouts: Vec<Box<Layout>> = vec![Box::new(Tile),
+                                         Box::new(MasterStack::default()),
+                                         Box::new(CenteredMaster::default())];
+    for layout in layouts {
+        let even = layout.layout_rects(config.clone(), 5, screen.clone());
+        let rects = layout.layout_rects_weighted(config.clone(), &[1, 1, 1, 3, 1], screen.clone());
+        assert!(area(&rects[3]) > area(&even[3]), "{}", layout.name());
+    }
+}
+
+#[test]
+fn weights_split_as_documented() {
+    let config = Rc::new(Config::default());
+    let screen = screens(&config)[0].clone();
+    let slack = 2 * config.border_width + 1;
+    // The stack of MasterStack is shared in proportion to the weights.
+    let rects = MasterStack::default()
+        .layout_rects_weighted(config.clone(), &[1, 1, 2, 1], screen.clone());
+    assert!((rects[2].height - 2 * rects[1].height).abs() <= 2 * slack, "{:?}", rects);
+    assert!((rects[3].height - rects[1].height).abs() <= slack, "{:?}", rects);
+    // Tile splits by neighbouring weights: the first of [1, 1, 1, 3] still gets half.
+    let rects = Tile.layout_rects_weighted(config.clone(), &[1, 1, 1, 3], screen.clone());
+    assert!((rects[0].width - screen.width / 2).abs() <= slack, "{:?}", rects);
+}
+
+#[test]
+fn magnifier_grows_the_focused_window() {
+    let config = Rc::new(Config::default());
+    let area = |r: &Rect| r.width * r.height;
+    for screen in screens(&config) {
+        let inner = MasterStack::default();
+        let magnifier = Magnifier::new(Box::new(inner.clone()), 1.5);
+        for n in 2..MAX_CLIENTS + 1 {
+            let plain = inner.layout_rects(config.clone(), n, screen.clone());
+            for focused in 0..n {
+                let rects = magnifier.focused_rects(config.clone(), n, focused, screen.clone());
+                assert_eq!(rects.len(), n);
+                assert!(inside(&rects[focused], &screen),
+                        "{:?} with {} clients gave {:?}",
+                        screen,
+                        n,
+                        rects[focused]);
+                if !is_empty(&plain[focused]) {
+                    assert!(area(&rects[focused]) > area(&plain[focused]),
+                            "{:?} with {} clients: {:?} is not bigger than {:?}",
+                            screen,
+                            n,
+                            rects[focused],
+                            plain[focused]);
+                }
+                for i in (0..n).filter(|&i| i != focused) {
+                    assert_eq!(rects[i], plain[i]);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn bsp_tree_follows_clients() {
+    let config = Rc::new(Config::default());
+    let screen = screens(&config)[0].clone();
+    let mut bsp = Bsp::default();
+    bsp.client_added(1, None);
+    bsp.client_added(2, Some(1));
+    bsp.handle_message(config.clone(), &LayoutMessage::Presel(Direction::Vertical));
+    bsp.client_added(3, Some(1));
+    bsp.client_added(4, Some(2));
+
+    let rects = bsp.window_rects(config.clone(), screen.clone());
+    let windows: Vec<_> = rects.iter().map(|&(w, _)| w).collect();
+    assert_eq!(windows, vec![1, 3, 2, 4]);
+    // 1 and 3 share the left half, one above the other.
+    assert_eq!(rects[0].1.x, rects[1].1.x);
+    assert!(rects[0].1.y < rects[1].1.y);
+
+    bsp.handle_message(config.clone(), &LayoutMessage::ResizeSplit(3, 0.1));
+    let resized = bsp.window_rects(config.clone(), screen.clone());
+    assert!(resized[1].1.height > rects[1].1.height);
+
+    // Removing a window gives its space to its sibling.
+    bsp.client_removed(3);
+    let rects = bsp.window_rects(config.clone(), screen.clone());
+    assert_eq!(rects.len(), 3);
+    assert_eq!(rects[0].1.height, screen.height);
+    bsp.client_removed(1);
+    bsp.client_removed(2);
+    bsp.client_removed(4);
+    assert!(bsp.window_rects(config, screen).is_empty());
+}